@@ -1,15 +1,28 @@
+mod output;
+
 use clap::{Args, Parser, Subcommand};
-use hex::decode;
-use std::fs;
-use std::path::PathBuf;
-use vcsv_lib::{Backend, Op};
+use output::{print_error, DiffOutput, Format, Output, PublicValuesOutput, Reveal, EXIT_ERROR};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::{env, fs, process};
+use vcsv_lib::{
+    decimal_points, equal_width_edges, result_commitment, Backend, Claim, Comparator, Input, Noise,
+    Op, ProofType, PublicValues,
+};
 use vcsv_script::{
-    execute, inclusion_proof, proof, verify, verify_inclusion, InclusionProofString,
+    file_root, for_each_leaf, inclusion_proof, parse_address, parse_expr, parse_hash, parse_query,
+    parse_signature, random_nonce, read_input, sign_root, signing_key, verify_inclusion,
+    verify_root_signature, BackendConfig, InclusionProofString, KeySource, Program, ProofBundle,
+    RootSignature, VcsvClient, VcsvError,
 };
 
 #[derive(Parser)]
 #[command(name = "vcsv", version, about = "Verifiable CSV analytics")]
 struct Cli {
+    /// Output format. `json` prints one JSON object per invocation, including on failure.
+    #[arg(long, value_enum, global = true, default_value = "text")]
+    pub format: Format,
     #[command(subcommand)]
     pub cmd: Command,
 }
@@ -18,41 +31,420 @@ struct Cli {
 pub enum Command {
     Execute(ExecuteArgs),
     Prove(ProveArgs),
+    Append(AppendArgs),
     Verify(VerifyArgs),
+    Reveal(RevealArgs),
+    DiffProof(DiffProofArgs),
     InclusionProof(InclusionProofArgs),
     VerifyInclusion(VerifyInclusionArgs),
+    SignRoot(SignRootArgs),
+    VerifyRootSignature(VerifyRootSignatureArgs),
+    Query(QueryArgs),
+}
+
+impl Command {
+    fn name(&self) -> &'static str {
+        match self {
+            Command::Execute(_) => "execute",
+            Command::Prove(_) => "prove",
+            Command::Append(_) => "append",
+            Command::Verify(_) => "verify",
+            Command::Reveal(_) => "reveal",
+            Command::DiffProof(_) => "diff-proof",
+            Command::InclusionProof(_) => "inclusion-proof",
+            Command::VerifyInclusion(_) => "verify-inclusion",
+            Command::SignRoot(_) => "sign-root",
+            Command::VerifyRootSignature(_) => "verify-root-signature",
+            Command::Query(_) => "query",
+        }
+    }
 }
 
+/// The ops that need `--y-col`.
+const PAIRWISE_OPS: [(&str, &str); 6] = [
+    ("op", "covariance"),
+    ("op", "correlation"),
+    ("op", "slope"),
+    ("op", "intercept"),
+    ("op", "weighted-mean"),
+    ("op", "sum-product"),
+];
+
+/// The ops that need `--k`.
+const TOP_K_OPS: [(&str, &str); 2] = [("op", "top-k"), ("op", "bottom-k")];
+
 #[derive(Args, Debug)]
 pub struct ExecuteArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct InputArgs {
     #[arg(long, value_enum)]
     pub op: Op,
     #[arg(long)]
     pub file: PathBuf,
+    #[arg(long, required_unless_present = "expr")]
+    pub col: Option<String>,
+    /// The second column of a pairwise statistic, regressed on `--col` by `slope` and
+    /// `intercept`, or the weights of `weighted-mean` and `sum-product`.
+    #[arg(long, visible_alias = "weight", required_if_eq_any = PAIRWISE_OPS)]
+    pub y_col: Option<String>,
+    /// Aggregate an expression of each row instead of a column, such as `price * qty - discount`.
+    #[arg(long, conflicts_with_all = ["col", "y_col"])]
+    pub expr: Option<String>,
+    #[command(flatten)]
+    pub bounds: BoundsArgs,
+    #[command(flatten)]
+    pub noise: NoiseArgs,
+    #[command(flatten)]
+    pub claim: ClaimArgs,
+    #[command(flatten)]
+    pub histogram: HistogramArgs,
+    #[command(flatten)]
+    pub top_k: TopKArgs,
+    /// Fail unless the result is computed over at least this many rows.
+    #[arg(long, default_value_t = 0)]
+    pub min_rows: u64,
+}
+
+impl InputArgs {
+    /// Reads the file and builds the program input the flags describe.
+    fn input(self) -> Result<Input, VcsvError> {
+        Ok(Input {
+            noise: self.noise.noise(&self.op, &self.bounds)?,
+            bounds: self.bounds.range_check(&self.op)?,
+            claim: self.claim.claim(&self.op)?,
+            edges: self.histogram.edges(&self.op, &self.bounds)?,
+            k: self.top_k.k.unwrap_or_default(),
+            reveal_rows: self.top_k.reveal_rows,
+            y_col: self.y_col,
+            expr: self.expr.as_deref().map(parse_expr).transpose()?,
+            min_rows: self.min_rows,
+            ..read_input(self.file, self.op, self.col.unwrap_or_default())?
+        })
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct BackendArgs {
+    #[arg(long, value_enum, default_value = "cpu")]
+    pub backend: Backend,
+    /// File holding the Succinct Prover Network private key.
     #[arg(long)]
-    pub col: String,
+    pub pkey_file: Option<PathBuf>,
+    /// Environment variable holding the network private key, used when no file is given. SP1
+    /// reads the key from `NETWORK_PRIVATE_KEY`. Prompts on the terminal without echoing the key
+    /// if neither is available.
+    #[arg(long, default_value = "NETWORK_PRIVATE_KEY")]
+    pub pkey_env: String,
+    #[arg(long, value_enum, default_value = "compressed")]
+    pub proof_type: ProofType,
+}
+
+impl BackendArgs {
+    fn config(&self) -> Result<BackendConfig, VcsvError> {
+        Ok(match self.backend {
+            Backend::Cpu => BackendConfig::Cpu,
+            Backend::Mock => BackendConfig::Mock,
+            Backend::Network => {
+                let source = match &self.pkey_file {
+                    Some(path) => KeySource::File(path.clone()),
+                    None if env::var_os(&self.pkey_env).is_some() => {
+                        KeySource::Env(self.pkey_env.clone())
+                    }
+                    None => KeySource::Prompt,
+                };
+                BackendConfig::Network {
+                    private_key: source.read()?,
+                }
+            }
+        })
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct BoundsArgs {
+    /// Smallest value a row may hold: checked by `range-check`, split by `--buckets`, and bounds
+    /// how much one row can change a noised result.
+    #[arg(long, allow_hyphen_values = true)]
+    pub lower: Option<i128>,
+    /// Largest value a row may hold.
+    #[arg(long, allow_hyphen_values = true)]
+    pub upper: Option<i128>,
+}
+
+impl BoundsArgs {
+    fn range_check(&self, op: &Op) -> Result<Option<(i128, i128)>, VcsvError> {
+        match (op, self.lower, self.upper) {
+            (Op::RangeCheck, Some(lower), Some(upper)) if lower <= upper => {
+                Ok(Some((lower, upper)))
+            }
+            (Op::RangeCheck, _, _) => Err(VcsvError::InvalidBounds),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct NoiseArgs {
+    /// Publish a differentially private sum or mean with this privacy budget ε, in steps of
+    /// 0.001.
+    #[arg(long, requires_all = ["lower", "upper", "noise_seed"], conflicts_with = "expr")]
+    pub epsilon: Option<f64>,
+    /// Secret 32-byte hex seed the noise is drawn from. Publish its keccak hash before proving.
+    #[arg(long, requires = "epsilon")]
+    pub noise_seed: Option<String>,
+}
+
+impl NoiseArgs {
+    fn noise(&self, op: &Op, bounds: &BoundsArgs) -> Result<Option<Noise>, VcsvError> {
+        let (Some(epsilon), Some(lower), Some(upper), Some(seed)) =
+            (self.epsilon, bounds.lower, bounds.upper, &self.noise_seed)
+        else {
+            return Ok(None);
+        };
+        // Other ops, such as `range-check`, would also commit their own bounds as `lower` and
+        // `upper`, and the program refuses to noise them.
+        if !matches!(op, Op::Sum | Op::Mean) {
+            return Err(VcsvError::InvalidNoise("only sums and means can be noised"));
+        }
+
+        let epsilon_millis = (epsilon * 1000.0).round();
+        if !(1.0..=u32::MAX as f64).contains(&epsilon_millis) {
+            return Err(VcsvError::InvalidNoise("epsilon must be at least 0.001"));
+        }
+        if lower >= upper {
+            return Err(VcsvError::InvalidNoise("lower must be below upper"));
+        }
+
+        Ok(Some(Noise {
+            epsilon_millis: epsilon_millis as u32,
+            lower,
+            upper,
+            seed: parse_hash(seed)?,
+        }))
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ClaimArgs {
+    /// Commit only whether the result compares this way with `--threshold`, hiding the result.
+    #[arg(long, value_enum, requires = "threshold")]
+    pub claim: Option<Comparator>,
+    /// The value the result is compared with, such as `1000000` or `52.5`.
+    #[arg(long, requires = "claim", allow_hyphen_values = true)]
+    pub threshold: Option<String>,
+}
+
+impl ClaimArgs {
+    fn claim(&self, op: &Op) -> Result<Option<Claim>, VcsvError> {
+        let (Some(comparator), Some(threshold)) = (self.claim, &self.threshold) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Claim {
+            comparator,
+            threshold: parse_decimal(threshold, decimal_points(op))
+                .ok_or_else(|| VcsvError::InvalidThreshold(threshold.clone()))?,
+        }))
+    }
+}
+
+/// Formats an integer scaled by `10^decimals` as a decimal number, the inverse of
+/// [`parse_decimal`].
+fn format_decimal(value: i128, decimals: u16) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let digits = format!("{digits:0>width$}", width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let sign = if value < 0 { "-" } else { "" };
+    match fraction {
+        "" => format!("{sign}{whole}"),
+        _ => format!("{sign}{whole}.{fraction}"),
+    }
+}
+
+/// The decimals a hidden result is disclosed with: none for a claim, whose result is 1 or 0.
+fn result_decimals(public_values: &PublicValues) -> u16 {
+    match public_values.comparator {
+        0 => public_values.decimal_points,
+        _ => 0,
+    }
+}
+
+/// Parses a decimal number into an integer scaled by `10^decimals`, rejecting numbers with
+/// more decimal places than that.
+fn parse_decimal(s: &str, decimals: u16) -> Option<i128> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.is_empty() || fraction.len() > decimals as usize {
+        return None;
+    }
+
+    let mut scaled = format!("{whole}{fraction}");
+    scaled.extend((fraction.len()..decimals as usize).map(|_| '0'));
+    if !scaled.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let value: i128 = scaled.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+#[derive(Args, Debug)]
+pub struct HistogramArgs {
+    /// Increasing bucket edges for `histogram`, such as `0,18,65,120`.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        allow_hyphen_values = true,
+        conflicts_with = "buckets"
+    )]
+    pub edges: Vec<i128>,
+    /// Split `--lower..=--upper` into this many equal-width buckets for `histogram`.
+    #[arg(long, requires_all = ["lower", "upper"])]
+    pub buckets: Option<u32>,
+}
+
+impl HistogramArgs {
+    fn edges(&self, op: &Op, bounds: &BoundsArgs) -> Result<Vec<i128>, VcsvError> {
+        if !matches!(op, Op::Histogram) {
+            return Ok(Vec::new());
+        }
+        let edges = match (self.buckets, bounds.lower, bounds.upper) {
+            (Some(buckets), Some(lower), Some(upper))
+                if buckets > 0 && upper.saturating_sub(lower) >= buckets as i128 =>
+            {
+                equal_width_edges(lower, upper, buckets)
+            }
+            (None, _, _) => self.edges.clone(),
+            _ => return Err(VcsvError::InvalidEdges),
+        };
+        if edges.len() < 2 || edges.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(VcsvError::InvalidEdges);
+        }
+        Ok(edges)
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct TopKArgs {
+    /// How many of the largest values `top-k`, or smallest values `bottom-k`, adds up.
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u64).range(1..),
+        required_if_eq_any = TOP_K_OPS
+    )]
+    pub k: Option<u64>,
+    /// Commit the selected rows' indices, Merkle leaves and values, not just their sum.
+    #[arg(long, requires = "k")]
+    pub reveal_rows: bool,
 }
 
 #[derive(Args, Debug)]
 pub struct ProveArgs {
-    #[arg(long, value_enum)]
-    pub op: Op,
+    #[command(flatten)]
+    pub input: InputArgs,
+    #[arg(long, default_value = "proof.json")]
+    pub out: PathBuf,
+    #[command(flatten)]
+    pub backend: BackendArgs,
+    /// Prove the file in chunks of this many rows (a power of two) and aggregate the chunk
+    /// proofs, for files too large to prove in one go.
+    #[arg(long)]
+    pub chunk_rows: Option<usize>,
+    /// A `sign-root` output over the file's root. The recovered signer is committed in the
+    /// proof as `signer`.
+    #[arg(long)]
+    pub signature: Option<PathBuf>,
+    /// Commit to the result with a random nonce instead of revealing it.
+    #[arg(long)]
+    pub hide_result: bool,
+    /// Where to write the hidden result and its nonce, for `reveal`.
+    #[arg(long, default_value = "reveal.json")]
+    pub reveal_out: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct QueryArgs {
     #[arg(long)]
     pub file: PathBuf,
+    /// The query, such as `SELECT region, AVG(price) FROM data WHERE qty > 1 GROUP BY region`.
+    #[arg(long)]
+    pub sql: String,
+    /// A file the query joins, repeated once per `JOIN` in the same order.
+    #[arg(long = "join", value_name = "FILE")]
+    pub joined: Vec<PathBuf>,
+    /// Fail unless every group holds at least this many rows.
+    #[arg(long, default_value_t = 0)]
+    pub min_rows: u64,
+    #[arg(long, default_value = "proof.json")]
+    pub out: PathBuf,
+    #[command(flatten)]
+    pub backend: BackendArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct AppendArgs {
+    /// The proof to extend, as written by `prove` or an earlier `append`.
+    #[arg(long)]
+    pub prev: PathBuf,
+    /// A CSV with the same header as the proven file, holding only the appended rows.
+    #[arg(long)]
+    pub file: PathBuf,
+    #[arg(long, value_enum, default_value = "sum")]
+    pub op: Op,
     #[arg(long)]
     pub col: String,
     #[arg(long, default_value = "proof.json")]
     pub out: PathBuf,
+    #[command(flatten)]
+    pub backend: BackendArgs,
+    /// A `sign-root` output over the root of the extended file.
+    #[arg(long)]
+    pub signature: Option<PathBuf>,
+    /// Fail unless the extended file holds at least this many rows.
+    #[arg(long, default_value_t = 0)]
+    pub min_rows: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    #[arg(long, default_value = "proof.json")]
+    pub proof: PathBuf,
     #[arg(long, value_enum, default_value = "cpu")]
     pub backend: Backend,
-    #[arg(long, required_if_eq("backend", "network"))]
-    pub pkey: Option<String>,
 }
 
 #[derive(Args, Debug)]
-pub struct VerifyArgs {
+pub struct RevealArgs {
     #[arg(long, default_value = "proof.json")]
     pub proof: PathBuf,
+    /// The disclosed result, such as `1050000` or `52.5`.
+    #[arg(long, allow_hyphen_values = true)]
+    pub result: String,
+    /// The nonce the result was committed with.
+    #[arg(long)]
+    pub nonce: String,
+    #[arg(long, value_enum, default_value = "cpu")]
+    pub backend: Backend,
+}
+
+#[derive(Args, Debug)]
+pub struct DiffProofArgs {
+    /// The original version of the file.
+    #[arg(long)]
+    pub old: PathBuf,
+    /// The corrected version of the file.
+    #[arg(long)]
+    pub new: PathBuf,
+    #[arg(long, default_value = "diff.json")]
+    pub out: PathBuf,
+    #[command(flatten)]
+    pub backend: BackendArgs,
 }
 
 #[derive(Args, Debug)]
@@ -75,25 +467,261 @@ pub struct VerifyInclusionArgs {
     pub row: usize,
 }
 
+#[derive(Args, Debug)]
+pub struct SignRootArgs {
+    /// The file whose root is signed.
+    #[arg(long)]
+    pub file: PathBuf,
+    /// File holding the provider's secp256k1 private key, hex encoded.
+    #[arg(long)]
+    pub key_file: Option<PathBuf>,
+    /// Environment variable holding the private key, used when no file is given.
+    /// Prompts on the terminal without echoing the key if neither is available.
+    #[arg(long, default_value = "VCSV_SIGNING_KEY")]
+    pub key_env: String,
+    #[arg(long, default_value = "root-signature.json")]
+    pub out: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyRootSignatureArgs {
+    #[arg(long, default_value = "root-signature.json")]
+    pub signature: PathBuf,
+    /// Check the signature against the root of this file.
+    #[arg(long, conflicts_with = "root")]
+    pub file: Option<PathBuf>,
+    /// Check the signature against this root, e.g. a proof's `fileRoot`.
+    #[arg(long)]
+    pub root: Option<String>,
+    /// Require the signature to be from this address. Required without `--file` or `--root`,
+    /// since the signature then only checks against the root it records itself.
+    #[arg(long, required_unless_present_any = ["file", "root"])]
+    pub signer: Option<String>,
+}
+
 fn main() {
     let args = Cli::parse();
-    println!("cmd={:?}", args.cmd);
-
-    match args.cmd {
-        Command::Execute(args) => execute(args.file, args.op, args.col),
-        Command::Prove(args) => proof(
-            args.file,
-            args.op,
-            args.col,
-            args.out,
-            args.backend,
-            args.pkey,
-        ),
-        Command::Verify(args) => verify(args.proof),
+    let command = args.cmd.name();
+
+    match run(args.cmd) {
+        Ok(output) => {
+            output.print(args.format);
+            process::exit(output.exit_code());
+        }
+        Err(e) => {
+            print_error(command, &e, args.format);
+            process::exit(EXIT_ERROR);
+        }
+    }
+}
+
+fn run(cmd: Command) -> Result<Output, VcsvError> {
+    Ok(match cmd {
+        Command::Execute(args) => {
+            let input = args.input.input()?;
+            let client = VcsvClient::new(BackendConfig::Cpu);
+            let execution = client.execute(&input)?;
+
+            Output::Execute {
+                public_values: PublicValuesOutput::from(&execution.public_values)
+                    .with_table(execution.table.as_ref())
+                    .with_expr(input.expr.as_ref()),
+                cycles: execution.report.total_instruction_count(),
+            }
+        }
+        Command::Prove(args) => {
+            let root = match &args.signature {
+                Some(_) => file_root(&args.input.file)?,
+                None => [0; 32],
+            };
+            let mut input = args.input.input()?;
+            input.signature = root_signature(args.signature.as_deref(), &root)?;
+            input.result_nonce = args.hide_result.then(random_nonce);
+            let client = VcsvClient::new(args.backend.config()?);
+            let proof_type = args.backend.proof_type;
+            let bundle = match args.chunk_rows {
+                Some(chunk_rows) => client.prove_chunked(&input, chunk_rows, proof_type)?,
+                None => client.prove(&input, proof_type)?,
+            };
+            bundle.write(&args.out)?;
+
+            let public_values = bundle.public_values()?;
+            let reveal = match &bundle.hidden_result {
+                Some(hidden) => {
+                    let reveal = Reveal {
+                        result: format_decimal(hidden.result, result_decimals(&public_values)),
+                        nonce: format!("0x{}", hex::encode(hidden.nonce)),
+                    };
+                    fs::write(&args.reveal_out, serde_json::to_string_pretty(&reveal)?)?;
+                    Some(args.reveal_out)
+                }
+                None => None,
+            };
+
+            Output::Prove {
+                proof_type: bundle.proof_type,
+                out: args.out,
+                public_values: PublicValuesOutput::from(&public_values)
+                    .with_table(bundle.table()?.as_ref())
+                    .with_expr(input.expr.as_ref()),
+                reveal,
+            }
+        }
+        Command::Query(args) => {
+            let mut input = read_input(args.file, Op::Query, "")?;
+            let query = parse_query(&args.sql)?;
+            if args.joined.len() != query.joins.len() {
+                return Err(VcsvError::InvalidQuery(format!(
+                    "the query joins {} files but --join gave {}",
+                    query.joins.len(),
+                    args.joined.len()
+                )));
+            }
+            input.query = Some(query);
+            input.joined = args.joined.iter().map(fs::read).collect::<Result<_, _>>()?;
+            input.min_rows = args.min_rows;
+            let client = VcsvClient::new(args.backend.config()?);
+            let bundle = client.prove(&input, args.backend.proof_type)?;
+            bundle.write(&args.out)?;
+
+            Output::Prove {
+                proof_type: bundle.proof_type,
+                out: args.out,
+                public_values: PublicValuesOutput::from(&bundle.public_values()?)
+                    .with_table(bundle.table()?.as_ref()),
+                reveal: None,
+            }
+        }
+        Command::Append(args) => {
+            let prev = ProofBundle::read(&args.prev)?;
+            let root = match &args.signature {
+                Some(_) => {
+                    let mut frontier = prev.frontier.clone().ok_or(VcsvError::MissingFrontier)?;
+                    for_each_leaf(BufReader::new(File::open(&args.file)?), |leaf| {
+                        frontier.push(leaf)
+                    })?;
+                    frontier.root().ok_or(VcsvError::EmptyCsv)?
+                }
+                None => [0; 32],
+            };
+            let mut input = read_input(args.file, args.op, args.col)?;
+            input.signature = root_signature(args.signature.as_deref(), &root)?;
+            input.min_rows = args.min_rows;
+            let client = VcsvClient::new(args.backend.config()?);
+            let bundle = client.append(&prev, &input, args.backend.proof_type)?;
+            bundle.write(&args.out)?;
+
+            Output::Append {
+                proof_type: bundle.proof_type,
+                prev: args.prev,
+                out: args.out,
+                appended: bundle.public_values()?.n_rows - prev.public_values()?.n_rows,
+                public_values: (&bundle.public_values()?).into(),
+            }
+        }
+        Command::Verify(args) => {
+            // Network proofs are ordinary SP1 proofs, so they are verified locally.
+            let backend = match args.backend {
+                Backend::Mock => BackendConfig::Mock,
+                Backend::Cpu | Backend::Network => BackendConfig::Cpu,
+            };
+            let bundle = ProofBundle::read(args.proof)?;
+
+            let client = VcsvClient::new(backend);
+            let verified = match bundle.program {
+                Program::Diff => client
+                    .verify_diff(&bundle)
+                    .map(|values| (None, Some(DiffOutput::new(&values, &bundle)))),
+                _ => client.verify(&bundle).and_then(|public_values| {
+                    let public_values = PublicValuesOutput::from(&public_values)
+                        .with_table(bundle.table()?.as_ref());
+                    Ok((Some(public_values), None))
+                }),
+            };
+
+            match verified {
+                Ok((public_values, diff)) => Output::Verify {
+                    verified: true,
+                    proof_type: bundle.proof_type,
+                    public_values,
+                    diff,
+                    reason: None,
+                },
+                Err(
+                    e @ (VcsvError::Verification(_)
+                    | VcsvError::ProofTypeMismatch { .. }
+                    | VcsvError::ProgramKeyMismatch
+                    | VcsvError::ChangesMismatch),
+                ) => Output::Verify {
+                    verified: false,
+                    proof_type: bundle.proof_type,
+                    public_values: None,
+                    diff: None,
+                    reason: Some(e.to_string()),
+                },
+                Err(e) => return Err(e),
+            }
+        }
+        Command::Reveal(args) => {
+            let backend = match args.backend {
+                Backend::Mock => BackendConfig::Mock,
+                Backend::Cpu | Backend::Network => BackendConfig::Cpu,
+            };
+            let bundle = ProofBundle::read(args.proof)?;
+            let nonce = parse_hash(&args.nonce)?;
+
+            let client = VcsvClient::new(backend);
+            let public_values = match client.verify(&bundle) {
+                Ok(public_values) => public_values,
+                Err(
+                    e @ (VcsvError::Verification(_)
+                    | VcsvError::ProofTypeMismatch { .. }
+                    | VcsvError::ProgramKeyMismatch),
+                ) => {
+                    return Ok(Output::Reveal {
+                        verified: false,
+                        result: args.result,
+                        public_values: None,
+                        reason: Some(e.to_string()),
+                    })
+                }
+                Err(e) => return Err(e),
+            };
+            if public_values.resultCommitment == [0u8; 32] {
+                return Err(VcsvError::ResultNotHidden);
+            }
+
+            let result = parse_decimal(&args.result, result_decimals(&public_values))
+                .ok_or_else(|| VcsvError::InvalidResult(args.result.clone()))?;
+            let verified = result_commitment(result, &nonce) == public_values.resultCommitment;
+            Output::Reveal {
+                verified,
+                result: args.result,
+                public_values: verified.then(|| (&public_values).into()),
+                reason: (!verified)
+                    .then(|| "result and nonce do not match the commitment".to_string()),
+            }
+        }
+        Command::DiffProof(args) => {
+            let client = VcsvClient::new(args.backend.config()?);
+            let bundle = client.prove_diff(
+                fs::read(args.old)?,
+                fs::read(args.new)?,
+                args.backend.proof_type,
+            )?;
+            bundle.write(&args.out)?;
+
+            Output::DiffProof {
+                proof_type: bundle.proof_type,
+                out: args.out,
+                diff: DiffOutput::new(&bundle.diff_values()?, &bundle),
+            }
+        }
         Command::InclusionProof(args) => {
-            let proof = inclusion_proof(args.file, args.row as usize); // returns MerkleProof { leaf, siblings }
+            let row = args.row as usize;
+            let proof = inclusion_proof(args.file, row)?;
 
-            let out = InclusionProofString {
+            let proof = InclusionProofString {
                 leaf: format!("0x{}", hex::encode(proof.leaf)),
                 siblings: proof
                     .siblings
@@ -102,27 +730,75 @@ fn main() {
                     .collect(),
             };
 
-            let json = serde_json::to_string_pretty(&out).unwrap();
+            if let Some(path) = &args.out {
+                fs::write(path, serde_json::to_string_pretty(&proof)?)?;
+            }
 
-            match args.out {
-                Some(path) => {
-                    fs::write(path, json).expect("couldn't write proof to file");
-                }
-                None => println!("{json}"),
+            Output::InclusionProof {
+                row,
+                proof,
+                out: args.out,
             }
         }
         Command::VerifyInclusion(args) => {
-            let json = fs::read_to_string(&args.proof).expect("failed to read proof file");
-            let inc_proof: InclusionProofString =
-                serde_json::from_str(&json).expect("invalid proof JSON");
+            let json = fs::read_to_string(&args.proof)?;
+            let inc_proof: InclusionProofString = serde_json::from_str(&json)?;
+            let root = parse_hash(&args.root)?;
+
+            Output::VerifyInclusion {
+                verified: verify_inclusion(&root, inc_proof, args.row)?,
+                row: args.row,
+                root: format!("0x{}", hex::encode(root)),
+            }
+        }
+        Command::SignRoot(args) => {
+            let source = match args.key_file {
+                Some(path) => KeySource::File(path),
+                None if env::var_os(&args.key_env).is_some() => KeySource::Env(args.key_env),
+                None => KeySource::Prompt,
+            };
+            let key = signing_key(&source.read()?)?;
+            let signature = sign_root(&file_root(&args.file)?, &key);
+            fs::write(&args.out, serde_json::to_string_pretty(&signature)?)?;
 
-            let mut root_arr = [0u8; 32];
-            let root_bytes = decode(args.root.trim_start_matches("0x")).expect("invalid root hex");
-            assert_eq!(root_bytes.len(), 32);
-            root_arr.copy_from_slice(&root_bytes);
+            Output::SignRoot {
+                signature,
+                out: args.out,
+            }
+        }
+        Command::VerifyRootSignature(args) => {
+            let signature: RootSignature =
+                serde_json::from_str(&fs::read_to_string(&args.signature)?)?;
+            let root = match (&args.file, &args.root) {
+                (Some(file), _) => file_root(file)?,
+                (None, Some(root)) => parse_hash(root)?,
+                // Only meaningful with `--signer`, which clap requires here.
+                (None, None) => parse_hash(&signature.root)?,
+            };
 
-            let ok = verify_inclusion(&root_arr, inc_proof, args.row);
-            println!("{}", if ok { "verified!" } else { "failed :(" });
+            let mut verified = verify_root_signature(&root, &signature)?;
+            if let Some(signer) = &args.signer {
+                verified &= parse_address(signer)? == parse_address(&signature.signer)?;
+            }
+
+            Output::VerifyRootSignature {
+                verified,
+                root: format!("0x{}", hex::encode(root)),
+                signer: signature.signer,
+            }
         }
+    })
+}
+
+/// Reads the signature bytes from a `sign-root` output, checking it signs `root` so a mismatch
+/// fails before proving rather than committing the wrong signer.
+fn root_signature(path: Option<&Path>, root: &[u8; 32]) -> Result<Option<Vec<u8>>, VcsvError> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let signature: RootSignature = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if !verify_root_signature(root, &signature)? {
+        return Err(VcsvError::SignatureMismatch);
     }
+    Ok(Some(parse_signature(&signature.signature)?.to_vec()))
 }
@@ -0,0 +1,580 @@
+//! What each subcommand reports, rendered as text for people or as JSON for pipelines.
+
+use crate::format_decimal;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::path::PathBuf;
+use vcsv_lib::{
+    comparator_to_u8, op_to_u8, Comparator, DiffValues, Expr, Op, ProofType, PublicValues,
+    RowChange, Table,
+};
+use vcsv_script::{InclusionProofString, ProofBundle, RootSignature};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+/// Exit code when a proof or inclusion proof was checked and did not verify.
+pub const EXIT_NOT_VERIFIED: i32 = 1;
+/// Exit code for every other failure: unreadable files, bad input, failed execution or proving.
+/// (2 is left to clap for usage errors.)
+pub const EXIT_ERROR: i32 = 3;
+
+#[derive(Debug, Serialize)]
+pub struct PublicValuesOutput {
+    pub file_root: String,
+    /// The address that signed `file_root`, or the zero address for an unsigned root.
+    pub signer: String,
+    pub op: u8,
+    pub col_hash: String,
+    /// The hash of the second column, for pairwise statistics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y_col_hash: Option<String>,
+    /// The hash of the canonical expression aggregated instead of a column.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expr_hash: Option<String>,
+    /// The canonical expression `expr_hash` is the hash of, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expr: Option<String>,
+    pub n_rows: u64,
+    pub min_rows: u64,
+    /// How many values top-k and bottom-k select.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub k: Option<u64>,
+    /// The committed integer, as a string so it survives JSON parsers without 128-bit integers.
+    pub result: String,
+    pub decimal_points: u16,
+    /// `result` scaled down by `decimal_points`, or 1 or 0 for a claim that holds or not.
+    pub value: f64,
+    /// The bounds values were checked against, for range checks and noised results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<(String, String)>,
+    /// Present when `result` is whether a claim about the result holds, rather than the result.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claim: Option<ClaimOutput>,
+    /// Present when the proof commits to `result` instead of revealing it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_commitment: Option<String>,
+    /// Present when `result` is differentially private rather than exact.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub noise: Option<NoiseOutput>,
+    /// The bucket counts of a histogram, with `result` counting the values outside every bucket.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub histogram: Option<HistogramOutput>,
+    /// The result table of a query, with `result` counting its rows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<QueryOutput>,
+    /// The rows top-k or bottom-k selected, in rank order, when they are revealed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<TopKOutput>,
+}
+
+impl PublicValuesOutput {
+    pub fn with_table(mut self, table: Option<&Table>) -> Self {
+        match table {
+            Some(Table::Histogram(histogram)) => {
+                self.histogram = Some(HistogramOutput {
+                    edges: histogram.edges.iter().map(i128::to_string).collect(),
+                    counts: histogram.counts.clone(),
+                })
+            }
+            Some(Table::Query(query)) => {
+                self.query = Some(QueryOutput {
+                    plan_hash: query.planHash.to_string(),
+                    join_roots: query.joinRoots.iter().map(ToString::to_string).collect(),
+                    columns: query.columns.clone(),
+                    rows: query
+                        .rows
+                        .iter()
+                        .map(|row| {
+                            let values = row.values.iter().zip(&query.decimal_points);
+                            row.keys
+                                .iter()
+                                .cloned()
+                                .chain(
+                                    values
+                                        .map(|(value, decimals)| format_decimal(*value, *decimals)),
+                                )
+                                .collect()
+                        })
+                        .collect(),
+                });
+            }
+            Some(Table::TopK(top_k)) => {
+                self.top_k = Some(TopKOutput {
+                    rows: top_k.rows.clone(),
+                    leaves: top_k.leaves.iter().map(ToString::to_string).collect(),
+                    values: top_k.values.iter().map(i128::to_string).collect(),
+                });
+            }
+            None => {}
+        }
+        self
+    }
+
+    pub fn with_expr(mut self, expr: Option<&Expr>) -> Self {
+        self.expr = expr.map(Expr::to_string);
+        self
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistogramOutput {
+    pub edges: Vec<String>,
+    pub counts: Vec<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryOutput {
+    /// The hash of the query's canonical form.
+    pub plan_hash: String,
+    /// The Merkle root of each joined file.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub join_roots: Vec<String>,
+    pub columns: Vec<String>,
+    /// Each row's keys followed by its aggregates, scaled down by their decimal points.
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TopKOutput {
+    /// Each selected row's index, as `inclusion-proof --row` takes it.
+    pub rows: Vec<u64>,
+    /// Each selected row's Merkle leaf under `file_root`.
+    pub leaves: Vec<String>,
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClaimOutput {
+    pub comparator: Comparator,
+    /// Scaled down by `decimal_points`.
+    pub threshold: f64,
+    /// Absent when the outcome is hidden behind `result_commitment`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holds: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NoiseOutput {
+    pub seed_hash: String,
+    pub epsilon: f64,
+    pub sensitivity: String,
+}
+
+impl From<&PublicValues> for PublicValuesOutput {
+    fn from(public_values: &PublicValues) -> Self {
+        let scale = 10_f64.powf(public_values.decimal_points as f64);
+        Self {
+            file_root: public_values.fileRoot.to_string(),
+            signer: public_values.signer.to_checksum(None),
+            op: public_values.op,
+            col_hash: public_values.colHash.to_string(),
+            y_col_hash: (public_values.yColHash != [0u8; 32])
+                .then(|| public_values.yColHash.to_string()),
+            expr_hash: (public_values.exprHash != [0u8; 32])
+                .then(|| public_values.exprHash.to_string()),
+            expr: None,
+            n_rows: public_values.n_rows,
+            min_rows: public_values.min_rows,
+            k: (public_values.k != 0).then_some(public_values.k),
+            result: public_values.result.to_string(),
+            decimal_points: public_values.decimal_points,
+            value: match public_values.comparator {
+                0 => public_values.result as f64 / scale,
+                _ => public_values.result as f64,
+            },
+            bounds: (public_values.op == op_to_u8(Op::RangeCheck)
+                || public_values.epsilon_millis != 0)
+                .then(|| {
+                    (
+                        public_values.lower.to_string(),
+                        public_values.upper.to_string(),
+                    )
+                }),
+            claim: Comparator::value_variants()
+                .iter()
+                .find(|comparator| comparator_to_u8(**comparator) == public_values.comparator)
+                .map(|comparator| ClaimOutput {
+                    comparator: *comparator,
+                    threshold: public_values.threshold as f64 / scale,
+                    holds: (public_values.resultCommitment == [0u8; 32])
+                        .then_some(public_values.result == 1),
+                }),
+            result_commitment: (public_values.resultCommitment != [0u8; 32])
+                .then(|| public_values.resultCommitment.to_string()),
+            histogram: None,
+            query: None,
+            top_k: None,
+            noise: (public_values.epsilon_millis != 0).then(|| NoiseOutput {
+                seed_hash: public_values.seedHash.to_string(),
+                epsilon: public_values.epsilon_millis as f64 / 1000.0,
+                sensitivity: public_values.sensitivity.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffOutput {
+    pub old_root: String,
+    pub new_root: String,
+    pub old_rows: u64,
+    pub new_rows: u64,
+    pub added: u64,
+    pub removed: u64,
+    pub modified: u64,
+    pub changes: Vec<RowChange>,
+}
+
+impl DiffOutput {
+    pub fn new(values: &DiffValues, bundle: &ProofBundle) -> Self {
+        Self {
+            old_root: values.oldRoot.to_string(),
+            new_root: values.newRoot.to_string(),
+            old_rows: values.oldRows,
+            new_rows: values.newRows,
+            added: values.added,
+            removed: values.removed,
+            modified: values.modified,
+            changes: bundle.changes.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Output {
+    Execute {
+        public_values: PublicValuesOutput,
+        cycles: u64,
+    },
+    Prove {
+        proof_type: ProofType,
+        out: PathBuf,
+        public_values: PublicValuesOutput,
+        /// Where the hidden result and its nonce were written.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reveal: Option<PathBuf>,
+    },
+    Append {
+        proof_type: ProofType,
+        prev: PathBuf,
+        out: PathBuf,
+        appended: u64,
+        public_values: PublicValuesOutput,
+    },
+    Verify {
+        verified: bool,
+        proof_type: ProofType,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        public_values: Option<PublicValuesOutput>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        diff: Option<DiffOutput>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    DiffProof {
+        proof_type: ProofType,
+        out: PathBuf,
+        diff: DiffOutput,
+    },
+    InclusionProof {
+        row: usize,
+        #[serde(flatten)]
+        proof: InclusionProofString,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        out: Option<PathBuf>,
+    },
+    VerifyInclusion {
+        verified: bool,
+        row: usize,
+        root: String,
+    },
+    SignRoot {
+        #[serde(flatten)]
+        signature: RootSignature,
+        out: PathBuf,
+    },
+    VerifyRootSignature {
+        verified: bool,
+        root: String,
+        signer: String,
+    },
+    Reveal {
+        verified: bool,
+        result: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        public_values: Option<PublicValuesOutput>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+}
+
+/// A hidden result and the nonce it was committed with, kept by the prover until the reveal.
+#[derive(Debug, Serialize)]
+pub struct Reveal {
+    pub result: String,
+    pub nonce: String,
+}
+
+impl Output {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Output::Verify {
+                verified: false, ..
+            }
+            | Output::VerifyInclusion {
+                verified: false, ..
+            }
+            | Output::VerifyRootSignature {
+                verified: false, ..
+            }
+            | Output::Reveal {
+                verified: false, ..
+            } => EXIT_NOT_VERIFIED,
+            _ => 0,
+        }
+    }
+
+    pub fn print(&self, format: Format) {
+        match format {
+            Format::Json => println!("{}", serde_json::to_string_pretty(self).unwrap()),
+            Format::Text => self.print_text(),
+        }
+    }
+
+    fn print_text(&self) {
+        match self {
+            Output::Execute {
+                public_values,
+                cycles,
+            } => {
+                println!("Program executed successfully in {cycles} cycles.");
+                print_public_values(public_values);
+            }
+            Output::Prove {
+                proof_type,
+                out,
+                public_values,
+                reveal,
+            } => {
+                println!(
+                    "Successfully generated {:?} proof at {}.",
+                    proof_type,
+                    out.display()
+                );
+                print_public_values(public_values);
+                if let Some(reveal) = reveal {
+                    println!(
+                        "The result is hidden; keep {} to reveal it later.",
+                        reveal.display()
+                    );
+                }
+            }
+            Output::Append {
+                proof_type,
+                prev,
+                out,
+                appended,
+                public_values,
+            } => {
+                println!(
+                    "Appended {appended} rows to {} and wrote {:?} proof at {}.",
+                    prev.display(),
+                    proof_type,
+                    out.display()
+                );
+                print_public_values(public_values);
+            }
+            Output::Verify {
+                verified: true,
+                proof_type,
+                public_values,
+                diff,
+                ..
+            } => {
+                println!("Successfully verified {:?} proof!", proof_type);
+                if let Some(public_values) = public_values {
+                    print_public_values(public_values);
+                }
+                if let Some(diff) = diff {
+                    print_diff(diff);
+                }
+            }
+            Output::DiffProof {
+                proof_type,
+                out,
+                diff,
+            } => {
+                println!(
+                    "Successfully generated {:?} diff proof at {}.",
+                    proof_type,
+                    out.display()
+                );
+                print_diff(diff);
+            }
+            Output::Verify { reason, .. } => {
+                println!("Proof did not verify: {}", reason.as_deref().unwrap_or(""));
+            }
+            Output::InclusionProof {
+                row,
+                out: Some(out),
+                ..
+            } => {
+                println!("Wrote inclusion proof for row {row} to {}.", out.display());
+            }
+            Output::InclusionProof {
+                proof, out: None, ..
+            } => {
+                println!("{}", serde_json::to_string_pretty(proof).unwrap());
+            }
+            Output::VerifyInclusion {
+                verified,
+                row,
+                root,
+            } => {
+                if *verified {
+                    println!("Row {row} is included in {root}.");
+                } else {
+                    println!("Row {row} is NOT included in {root}.");
+                }
+            }
+            Output::SignRoot { signature, out } => {
+                println!(
+                    "Signed {} as {} and wrote the signature to {}.",
+                    signature.root,
+                    signature.signer,
+                    out.display()
+                );
+            }
+            Output::VerifyRootSignature {
+                verified,
+                root,
+                signer,
+            } => {
+                if *verified {
+                    println!("{root} is signed by {signer}.");
+                } else {
+                    println!("{root} is NOT signed by {signer}.");
+                }
+            }
+            Output::Reveal {
+                verified: true,
+                result,
+                public_values,
+                ..
+            } => {
+                println!("The proof commits to result {result}.");
+                if let Some(public_values) = public_values {
+                    print_public_values(public_values);
+                }
+            }
+            Output::Reveal { result, reason, .. } => {
+                println!(
+                    "Result {result} is NOT the one the proof commits to: {}",
+                    reason.as_deref().unwrap_or("")
+                );
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorOutput<'a> {
+    command: &'a str,
+    error: String,
+}
+
+/// Reports a failed subcommand on stdout (JSON) or stderr (text).
+pub fn print_error(command: &str, error: &dyn std::fmt::Display, format: Format) {
+    match format {
+        Format::Json => {
+            let output = ErrorOutput {
+                command,
+                error: error.to_string(),
+            };
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        Format::Text => eprintln!("error: {error}"),
+    }
+}
+
+fn print_public_values(public_values: &PublicValuesOutput) {
+    println!("fileRoot: {}", public_values.file_root);
+    println!("signer: {}", public_values.signer);
+    println!("op: {}", public_values.op);
+    println!("colHash: {}", public_values.col_hash);
+    if let Some(y_col_hash) = &public_values.y_col_hash {
+        println!("yColHash: {y_col_hash}");
+    }
+    if let Some(expr_hash) = &public_values.expr_hash {
+        println!("exprHash: {expr_hash}");
+    }
+    if let Some(expr) = &public_values.expr {
+        println!("expr: {expr}");
+    }
+    println!("n_rows: {}", public_values.n_rows);
+    if let Some(k) = public_values.k {
+        println!("k: {k}");
+    }
+    match (&public_values.result_commitment, &public_values.claim) {
+        (Some(commitment), _) => println!("resultCommitment: {commitment}"),
+        (None, Some(claim)) => println!(
+            "claim: result {} {}: {}",
+            claim.comparator.to_possible_value().unwrap().get_name(),
+            claim.threshold,
+            claim.holds.unwrap_or_default()
+        ),
+        (None, None) => println!("result: {}", public_values.value),
+    }
+    if let Some((lower, upper)) = &public_values.bounds {
+        println!("bounds: [{lower}, {upper}]");
+    }
+    if let Some(histogram) = &public_values.histogram {
+        let last = histogram.counts.len().saturating_sub(1);
+        for (i, count) in histogram.counts.iter().enumerate() {
+            let close = if i == last { ']' } else { ')' };
+            println!(
+                "  [{}, {}{close}: {count}",
+                histogram.edges[i],
+                histogram.edges[i + 1]
+            );
+        }
+    }
+    if let Some(top_k) = &public_values.top_k {
+        for ((row, leaf), value) in top_k.rows.iter().zip(&top_k.leaves).zip(&top_k.values) {
+            println!("  row {row}: {value} (leaf {leaf})");
+        }
+    }
+    if let Some(query) = &public_values.query {
+        println!("planHash: {}", query.plan_hash);
+        for root in &query.join_roots {
+            println!("joinRoot: {root}");
+        }
+        println!("  {}", query.columns.join(" | "));
+        for row in &query.rows {
+            println!("  {}", row.join(" | "));
+        }
+    }
+    if let Some(noise) = &public_values.noise {
+        println!(
+            "noise: epsilon {}, sensitivity {}, seedHash {}",
+            noise.epsilon, noise.sensitivity, noise.seed_hash
+        );
+    }
+}
+
+fn print_diff(diff: &DiffOutput) {
+    println!("oldRoot: {} ({} rows)", diff.old_root, diff.old_rows);
+    println!("newRoot: {} ({} rows)", diff.new_root, diff.new_rows);
+    println!(
+        "changed rows: {} added, {} removed, {} modified",
+        diff.added, diff.removed, diff.modified
+    );
+    for change in &diff.changes {
+        println!("  {:?} row {}", change.kind, change.row);
+    }
+}
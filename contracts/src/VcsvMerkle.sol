@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// @title VcsvMerkle
+/// @notice Verifies that a CSV row is part of a dataset committed to by a vcsv `fileRoot`.
+/// @dev Mirrors `vcsv_script::verify_inclusion`. Leaves are `keccak256` of the row with every
+///      cell trimmed and joined by `,`. Parents are `keccak256(left || right)`, and the last node
+///      of an odd-sized level is paired with itself, so its sibling in a proof is its own hash.
+library VcsvMerkle {
+    /// @notice Hashes a canonical row (`id,price,qty` style, no surrounding whitespace).
+    function leafHash(bytes memory row) internal pure returns (bytes32) {
+        return keccak256(row);
+    }
+
+    /// @notice Returns true if `leaf` at position `index` folds up to `root` along `siblings`.
+    function verify(bytes32 root, bytes32 leaf, bytes32[] memory siblings, uint256 index)
+        internal
+        pure
+        returns (bool)
+    {
+        return processProof(leaf, siblings, index) == root;
+    }
+
+    /// @notice Calldata variant of {verify}.
+    function verifyCalldata(bytes32 root, bytes32 leaf, bytes32[] calldata siblings, uint256 index)
+        internal
+        pure
+        returns (bool)
+    {
+        return processProofCalldata(leaf, siblings, index) == root;
+    }
+
+    /// @notice Returns true if the raw `row` bytes at position `index` are committed to by `root`.
+    function verifyRow(bytes32 root, bytes memory row, bytes32[] memory siblings, uint256 index)
+        internal
+        pure
+        returns (bool)
+    {
+        return verify(root, leafHash(row), siblings, index);
+    }
+
+    /// @notice Recomputes the root implied by `leaf`, `siblings` and `index`.
+    function processProof(bytes32 leaf, bytes32[] memory siblings, uint256 index)
+        internal
+        pure
+        returns (bytes32 running)
+    {
+        running = leaf;
+        for (uint256 i = 0; i < siblings.length; i++) {
+            if (index % 2 == 0) {
+                running = _hashPair(running, siblings[i]);
+            } else {
+                running = _hashPair(siblings[i], running);
+            }
+            index /= 2;
+        }
+    }
+
+    /// @notice Calldata variant of {processProof}, which copies `siblings` to memory.
+    function processProofCalldata(bytes32 leaf, bytes32[] calldata siblings, uint256 index)
+        internal
+        pure
+        returns (bytes32)
+    {
+        return processProof(leaf, siblings, index);
+    }
+
+    function _hashPair(bytes32 left, bytes32 right) private pure returns (bytes32 value) {
+        assembly ("memory-safe") {
+            mstore(0x00, left)
+            mstore(0x20, right)
+            value := keccak256(0x00, 0x40)
+        }
+    }
+}
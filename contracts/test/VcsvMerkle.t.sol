@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {VcsvMerkle} from "../src/VcsvMerkle.sol";
+
+/// @dev The subset of Foundry cheatcodes used to load the Rust-generated vectors.
+interface Vm {
+    function projectRoot() external view returns (string memory);
+    function readFile(string calldata path) external view returns (string memory);
+    function parseJsonUint(string calldata json, string calldata key) external pure returns (uint256);
+    function parseJsonBytes(string calldata json, string calldata key) external pure returns (bytes memory);
+    function parseJsonBytes32(string calldata json, string calldata key) external pure returns (bytes32);
+    function parseJsonBytes32Array(string calldata json, string calldata key)
+        external
+        pure
+        returns (bytes32[] memory);
+    function toString(uint256 value) external pure returns (string memory);
+}
+
+/// @notice Checks `VcsvMerkle` against the vectors written by `cargo run --bin merkle-vectors`.
+contract VcsvMerkleTest {
+    bool public IS_TEST = true;
+
+    Vm private constant vm = Vm(address(uint160(uint256(keccak256("hevm cheat code")))));
+
+    string private json;
+    uint256 private count;
+
+    function setUp() public {
+        json = vm.readFile(string.concat(vm.projectRoot(), "/src/fixtures/merkle-vectors.json"));
+        count = vm.parseJsonUint(json, ".count");
+        require(count > 0, "no vectors");
+    }
+
+    function testRustVectorsVerify() public view {
+        for (uint256 i = 0; i < count; i++) {
+            (bytes32 root, bytes memory row, bytes32 leaf, bytes32[] memory siblings, uint256 index) = _vector(i);
+
+            require(VcsvMerkle.leafHash(row) == leaf, "leaf hash mismatch");
+            require(VcsvMerkle.processProof(leaf, siblings, index) == root, "root mismatch");
+            require(VcsvMerkle.verifyRow(root, row, siblings, index), "row not verified");
+        }
+    }
+
+    function testCalldataVariantAgrees() public view {
+        for (uint256 i = 0; i < count; i++) {
+            (bytes32 root,, bytes32 leaf, bytes32[] memory siblings, uint256 index) = _vector(i);
+
+            require(this.verifyExternal(root, leaf, siblings, index), "calldata variant disagrees");
+        }
+    }
+
+    function testRejectsTamperedSibling() public view {
+        for (uint256 i = 0; i < count; i++) {
+            (bytes32 root,, bytes32 leaf, bytes32[] memory siblings, uint256 index) = _vector(i);
+            if (siblings.length == 0) continue;
+
+            siblings[0] = siblings[0] ^ bytes32(uint256(1));
+            require(!VcsvMerkle.verify(root, leaf, siblings, index), "tampered sibling verified");
+        }
+    }
+
+    function testRejectsWrongRow() public view {
+        for (uint256 i = 0; i < count; i++) {
+            (bytes32 root, bytes memory row,, bytes32[] memory siblings, uint256 index) = _vector(i);
+
+            bytes memory tampered = bytes.concat(row, ",");
+            require(!VcsvMerkle.verifyRow(root, tampered, siblings, index), "tampered row verified");
+        }
+    }
+
+    function verifyExternal(bytes32 root, bytes32 leaf, bytes32[] calldata siblings, uint256 index)
+        external
+        pure
+        returns (bool)
+    {
+        return VcsvMerkle.verifyCalldata(root, leaf, siblings, index);
+    }
+
+    function _vector(uint256 i)
+        private
+        view
+        returns (bytes32 root, bytes memory row, bytes32 leaf, bytes32[] memory siblings, uint256 index)
+    {
+        string memory key = string.concat(".vectors[", vm.toString(i), "]");
+        root = vm.parseJsonBytes32(json, string.concat(key, ".root"));
+        row = vm.parseJsonBytes(json, string.concat(key, ".row"));
+        leaf = vm.parseJsonBytes32(json, string.concat(key, ".leaf"));
+        siblings = vm.parseJsonBytes32Array(json, string.concat(key, ".siblings"));
+        index = vm.parseJsonUint(json, string.concat(key, ".index"));
+    }
+}
@@ -0,0 +1,179 @@
+//! Extending a proof of an append-only file with newly appended rows.
+//!
+//! The append program recursively verifies the previous proof, checks that the Merkle frontier
+//! it is given rebuilds the previous `fileRoot`, and pushes only the new rows. Besides the usual
+//! [`PublicValues`] it commits [`AppendValues`], which carry the exact running sum forward so the
+//! next append can continue from it.
+
+use crate::{
+    check_cohort, hash, hash_row, mean, op_to_u8, parse_csv, parse_i128, signer, vkey_bytes,
+    MerkleFrontier, Op, PublicValues,
+};
+use alloy_sol_types::{sol, SolType};
+use serde::{Deserialize, Serialize};
+
+sol! {
+    struct AppendValues {
+        bytes32 programs;
+        int128 sum;
+    }
+}
+
+/// Verifying key digests of every program a proof chain may contain.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProgramKeys {
+    pub vcsv: [u32; 8],
+    pub chunk: [u32; 8],
+    pub aggregate: [u32; 8],
+    pub append: [u32; 8],
+}
+
+impl ProgramKeys {
+    /// The digest committed as `AppendValues.programs`.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut bytes = Vec::with_capacity(128);
+        for vkey in [&self.vcsv, &self.chunk, &self.aggregate, &self.append] {
+            bytes.extend_from_slice(&vkey_bytes(vkey));
+        }
+        hash(&bytes)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AppendInput {
+    pub keys: ProgramKeys,
+    /// Verifying key digest of the program that produced the previous proof.
+    pub prev_vkey: [u32; 8],
+    /// Everything the previous proof committed.
+    pub prev_public_values: Vec<u8>,
+    /// The frontier of the previous file's tree. Checked against the previous `fileRoot`.
+    pub frontier: MerkleFrontier,
+    /// A header line followed by the appended rows.
+    pub csv: Vec<u8>,
+    pub col: String,
+    pub op: Op,
+    /// The data provider's signature over the extended file's root, if any.
+    #[serde(default)]
+    pub signature: Option<Vec<u8>>,
+    /// The fewest rows the extended file may hold.
+    #[serde(default)]
+    pub min_rows: u64,
+}
+
+/// Computes what the append program commits, along with the frontier of the extended file.
+/// Panics if the previous proof cannot be extended.
+pub fn append(input: AppendInput) -> (PublicValues, AppendValues, MerkleFrontier) {
+    let AppendInput {
+        keys,
+        prev_vkey,
+        prev_public_values,
+        mut frontier,
+        csv,
+        col,
+        op,
+        signature,
+        min_rows,
+    } = input;
+
+    let size = PublicValues::ENCODED_SIZE.unwrap();
+    assert!(
+        prev_public_values.len() >= size,
+        "previous public values are too short"
+    );
+    let (prev, trailer) = prev_public_values.split_at(size);
+    let prev = PublicValues::abi_decode(prev).expect("invalid previous public values");
+
+    // Only a sum proof, or an earlier append, pins down the exact running sum.
+    let mut sum = if prev_vkey == keys.append {
+        let values = AppendValues::abi_decode(trailer).expect("invalid previous append values");
+        assert_eq!(
+            values.programs,
+            keys.digest(),
+            "previous proof trusts different programs"
+        );
+        values.sum
+    } else {
+        if prev_vkey == keys.aggregate {
+            assert_eq!(
+                trailer,
+                vkey_bytes(&keys.chunk),
+                "previous proof aggregates a different chunk program"
+            );
+        } else {
+            assert_eq!(
+                prev_vkey, keys.vcsv,
+                "previous proof is from an unknown program"
+            );
+            assert!(trailer.is_empty(), "unexpected previous public values");
+        }
+        assert_eq!(
+            prev.op,
+            op_to_u8(Op::Sum),
+            "previous proof must be a sum or an append"
+        );
+        prev.result
+    };
+
+    assert_eq!(
+        prev.epsilon_millis, 0,
+        "previous proof publishes a noised result"
+    );
+    assert_eq!(
+        prev.comparator, 0,
+        "previous proof publishes a claim, not a sum"
+    );
+    assert_eq!(
+        prev.resultCommitment, [0u8; 32],
+        "previous proof hides its result"
+    );
+    let col_hash = hash(col.as_bytes());
+    assert_eq!(
+        prev.colHash, col_hash,
+        "previous proof is over a different column"
+    );
+    assert_eq!(
+        frontier.root(),
+        Some(prev.fileRoot.0),
+        "frontier does not match the previous root"
+    );
+    assert_eq!(
+        frontier.leaves(),
+        prev.n_rows,
+        "frontier does not match the previous row count"
+    );
+
+    let csv = parse_csv(csv, Some(&col));
+    for (row, value) in csv.rows().zip(csv.column()) {
+        frontier.push(hash_row(row));
+        sum = sum.checked_add(parse_i128(value)).expect("sum overflow");
+    }
+
+    let n_rows = frontier.leaves();
+    let (n_rows, result, decimal_points) = match op {
+        Op::Sum => (n_rows, sum, 0),
+        Op::Mean => mean(n_rows, sum),
+        _ => {
+            panic!("only sums and means can be extended")
+        }
+    };
+    check_cohort(n_rows, min_rows);
+
+    let file_root = frontier.root().unwrap();
+    let public = PublicValues {
+        fileRoot: file_root.into(),
+        signer: signer(&file_root, signature.as_deref()),
+        op: op_to_u8(op),
+        colHash: col_hash.into(),
+        n_rows,
+        min_rows,
+        result,
+        decimal_points,
+        ..Default::default()
+    };
+    let values = AppendValues {
+        programs: keys.digest().into(),
+        sum,
+    };
+
+    (public, values, frontier)
+}
@@ -0,0 +1,261 @@
+//! Proving a large CSV in chunks of rows and aggregating the chunk proofs.
+//!
+//! Every chunk but the last holds exactly `2^chunk_height` rows, so each chunk's Merkle root is
+//! a node of the whole file's tree. The aggregation program rebuilds `fileRoot` from those nodes
+//! and combines the per-chunk partial results into the same [`PublicValues`] a single-shot proof
+//! commits.
+//!
+//! A chunked median never sends the column to the aggregation program. The prover picks the two
+//! middle values of the whole column as pivots, each chunk proof commits how many of its values
+//! lie below and at each pivot, and the aggregation program checks from those counts alone that
+//! the pivots sit at the middle ranks.
+
+use crate::{
+    check_cohort, frontier, hash, hash_pair, hide_result, mean, median_of_middle, op_to_u8,
+    parse_csv, parse_i128, signer, sum_col, Claim, MerkleFrontier, Op, PublicValues,
+};
+use alloy_sol_types::sol;
+use serde::{Deserialize, Serialize};
+
+sol! {
+    #[derive(Default)]
+    struct ChunkValues {
+        bytes32 headerHash;
+        bytes32 colHash;
+        uint8 op;
+        uint64 firstRow;
+        uint64 n_rows;
+        bytes32 root;
+        uint8 height;
+        int128 sum;
+        int128 lowPivot;
+        int128 highPivot;
+        uint64 belowLow;
+        uint64 throughLow;
+        uint64 belowHigh;
+        uint64 throughHigh;
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ChunkInput {
+    /// The header line followed by the rows of the chunk.
+    pub csv: Vec<u8>,
+    pub col: String,
+    pub op: Op,
+    /// Index of the chunk's first row in the whole file.
+    pub first_row: u64,
+    /// The two middle values of the whole column, counted against by [`Op::Median`] chunks.
+    #[serde(default)]
+    pub pivots: Option<[i128; 2]>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AggregateInput {
+    /// Digest of the chunk program's verifying key.
+    pub chunk_vkey: [u32; 8],
+    /// Every chunk but the last holds `2^chunk_height` rows.
+    pub chunk_height: u8,
+    pub col: String,
+    pub op: Op,
+    /// The public values committed by each chunk proof, in row order.
+    pub chunks: Vec<Vec<u8>>,
+    /// The data provider's signature over the whole file's root, if any.
+    #[serde(default)]
+    pub signature: Option<Vec<u8>>,
+    /// The fewest rows the whole file may hold.
+    #[serde(default)]
+    pub min_rows: u64,
+    /// Commit only whether the result satisfies this claim.
+    #[serde(default)]
+    pub claim: Option<Claim>,
+    /// Commit `keccak(result || nonce)` instead of the result.
+    #[serde(default)]
+    pub result_nonce: Option<[u8; 32]>,
+}
+
+/// Splits a CSV into chunks of `chunk_rows` data rows, each starting with the header line.
+/// Returns the index of each chunk's first row along with the chunk.
+pub fn split_chunks(csv: &[u8], chunk_rows: usize) -> Vec<(u64, Vec<u8>)> {
+    let mut lines = csv.split(|b| *b == b'\n');
+    let header = lines.next().unwrap_or_default();
+    let rows: Vec<&[u8]> = lines.filter(|line| !line.is_empty()).collect();
+
+    rows.chunks(chunk_rows)
+        .enumerate()
+        .map(|(i, rows)| {
+            let mut chunk = header.to_vec();
+            for row in rows {
+                chunk.push(b'\n');
+                chunk.extend_from_slice(row);
+            }
+            ((i * chunk_rows) as u64, chunk)
+        })
+        .collect()
+}
+
+/// The values at ranks `(n - 1) / 2` and `n / 2` of a column split into chunks, which a chunked
+/// median's chunks count against. They are equal when the column has an odd number of rows.
+pub fn median_pivots(chunks: &[(u64, Vec<u8>)], col: &str) -> [i128; 2] {
+    let mut values: Vec<i128> = chunks
+        .iter()
+        .flat_map(|(_, csv)| {
+            let csv = parse_csv(csv.clone(), Some(col));
+            csv.column().map(parse_i128).collect::<Vec<_>>()
+        })
+        .collect();
+    assert!(!values.is_empty(), "no elements");
+
+    let n = values.len();
+    let high = *values.select_nth_unstable(n / 2).1;
+    let low = *values[..=n / 2].select_nth_unstable((n - 1) / 2).1;
+    [low, high]
+}
+
+/// Computes what the chunk program commits for a chunk.
+pub fn chunk_values(input: ChunkInput) -> ChunkValues {
+    let ChunkInput {
+        csv,
+        col,
+        op,
+        first_row,
+        pivots,
+    } = input;
+    let csv = parse_csv(csv, Some(&col));
+
+    let frontier = frontier(&csv);
+    let n_rows = frontier.leaves();
+    let root = frontier.root().expect("no rows");
+    let height = n_rows.next_power_of_two().trailing_zeros() as u8;
+
+    let mut chunk = ChunkValues {
+        headerHash: hash(csv.headers().as_bytes()).into(),
+        colHash: hash(col.as_bytes()).into(),
+        op: op_to_u8(op.clone()),
+        firstRow: first_row,
+        n_rows,
+        root: root.into(),
+        height,
+        ..Default::default()
+    };
+    match op {
+        Op::Sum | Op::Mean => chunk.sum = sum_col(&csv).1,
+        Op::Median => {
+            let [low, high] = pivots.expect("a chunked median needs the pivots");
+            chunk.lowPivot = low;
+            chunk.highPivot = high;
+            for value in csv.column().map(parse_i128) {
+                chunk.belowLow += (value < low) as u64;
+                chunk.throughLow += (value <= low) as u64;
+                chunk.belowHigh += (value < high) as u64;
+                chunk.throughHigh += (value <= high) as u64;
+            }
+        }
+        _ => panic!("only sums, means and medians can be chunked"),
+    }
+
+    chunk
+}
+
+/// Combines the values committed by consecutive chunks into the values a single-shot proof over
+/// the whole file commits. Panics if the chunks do not cover the file contiguously.
+pub fn aggregate(input: &AggregateInput, chunks: &[ChunkValues]) -> PublicValues {
+    assert!(!chunks.is_empty(), "no chunks");
+
+    let col_hash = hash(input.col.as_bytes());
+    let op = op_to_u8(input.op.clone());
+    let full = 1u64 << input.chunk_height;
+
+    let mut frontier = MerkleFrontier::new();
+    let mut n_rows = 0u64;
+    let mut sum = 0i128;
+    let (mut below_low, mut through_low, mut below_high, mut through_high) = (0, 0, 0, 0);
+    for (i, chunk) in chunks.iter().enumerate() {
+        assert_eq!(
+            chunk.headerHash, chunks[0].headerHash,
+            "chunks have different headers"
+        );
+        assert_eq!(chunk.colHash, col_hash, "chunk is over a different column");
+        assert_eq!(chunk.op, op, "chunk computed a different op");
+        assert_eq!(chunk.firstRow, n_rows, "chunks are not contiguous");
+        if i + 1 < chunks.len() {
+            assert_eq!(chunk.n_rows, full, "only the last chunk may be short");
+        } else {
+            assert!(chunk.n_rows <= full, "last chunk is too long");
+        }
+
+        n_rows += chunk.n_rows;
+        sum = sum.checked_add(chunk.sum).expect("sum overflow");
+        if let Op::Median = input.op {
+            assert!(
+                chunk.lowPivot == chunks[0].lowPivot && chunk.highPivot == chunks[0].highPivot,
+                "chunks counted against different pivots"
+            );
+            below_low += chunk.belowLow;
+            through_low += chunk.throughLow;
+            below_high += chunk.belowHigh;
+            through_high += chunk.throughHigh;
+        }
+
+        // A short last chunk is a smaller tree; in the file's tree it is paired with itself
+        // until it reaches the height of a full chunk.
+        let mut node = chunk.root.0;
+        if chunks.len() > 1 {
+            for _ in chunk.height..input.chunk_height {
+                node = hash_pair(&node, &node);
+            }
+        }
+        frontier.push(node);
+    }
+
+    let (n_rows, result, decimal_points) = match input.op {
+        Op::Sum => (n_rows, sum, 0),
+        Op::Mean => mean(n_rows, sum),
+        Op::Median => {
+            // A pivot is the value at rank r exactly when fewer than r + 1 values lie below it
+            // and more than r lie at or below it.
+            let (low_rank, high_rank) = ((n_rows - 1) / 2, n_rows / 2);
+            assert!(
+                below_low <= low_rank
+                    && low_rank < through_low
+                    && below_high <= high_rank
+                    && high_rank < through_high,
+                "the pivots are not the middle values"
+            );
+            median_of_middle(n_rows, chunks[0].lowPivot, chunks[0].highPivot)
+        }
+        _ => panic!("only sums, means and medians can be chunked"),
+    };
+
+    check_cohort(n_rows, input.min_rows);
+
+    let file_root = frontier.root().unwrap();
+    let mut public = PublicValues {
+        fileRoot: file_root.into(),
+        signer: signer(&file_root, input.signature.as_deref()),
+        op,
+        colHash: col_hash.into(),
+        n_rows,
+        min_rows: input.min_rows,
+        result,
+        decimal_points,
+        ..Default::default()
+    };
+    if let Some(claim) = &input.claim {
+        claim.apply(&mut public);
+    }
+    if let Some(nonce) = &input.result_nonce {
+        hide_result(&mut public, nonce);
+    }
+    public
+}
+
+/// The bytes the aggregation program appends to the ABI-encoded [`PublicValues`]: the chunk
+/// program's verifying key digest, so verifiers can check which program proved the chunks.
+pub fn vkey_bytes(vkey: &[u32; 8]) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (word, out) in vkey.iter().zip(bytes.chunks_mut(4)) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    bytes
+}
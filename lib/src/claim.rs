@@ -0,0 +1,65 @@
+//! Claims about a result, such as "revenue > 1M", proven without revealing the result itself.
+//!
+//! The program computes the result as usual, then commits the claim's comparator and threshold
+//! and replaces `result` with 1 if the claim holds and 0 otherwise.
+
+use crate::PublicValues;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Comparator {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Claim {
+    pub comparator: Comparator,
+    /// Scaled by the result's `decimal_points`, like the result it is compared with.
+    pub threshold: i128,
+}
+
+impl Comparator {
+    /// Whether `a` compares to `b` as the comparator says.
+    pub fn holds(self, a: i128, b: i128) -> bool {
+        match self {
+            Comparator::Gt => a > b,
+            Comparator::Ge => a >= b,
+            Comparator::Lt => a < b,
+            Comparator::Le => a <= b,
+            Comparator::Eq => a == b,
+            Comparator::Ne => a != b,
+        }
+    }
+}
+
+impl Claim {
+    pub fn holds(&self, result: i128) -> bool {
+        self.comparator.holds(result, self.threshold)
+    }
+
+    /// Commits the claim and whether it holds in place of the exact result.
+    pub fn apply(&self, public: &mut PublicValues) {
+        public.result = self.holds(public.result) as i128;
+        public.comparator = comparator_to_u8(self.comparator);
+        public.threshold = self.threshold;
+    }
+}
+
+/// The committed `comparator`. Zero means the result is exact rather than a claim.
+pub fn comparator_to_u8(comparator: Comparator) -> u8 {
+    match comparator {
+        Comparator::Gt => 1,
+        Comparator::Ge => 2,
+        Comparator::Lt => 3,
+        Comparator::Le => 4,
+        Comparator::Eq => 5,
+        Comparator::Ne => 6,
+    }
+}
@@ -0,0 +1,24 @@
+//! Hidden results, committed now and revealed later.
+//!
+//! The program commits `keccak(result || nonce)` as `resultCommitment` and zero as `result`.
+//! The result is packed as 16 big-endian bytes, like Solidity's `abi.encodePacked(int128,
+//! bytes32)`, so a contract can check a reveal as well.
+
+use crate::PublicValues;
+use tiny_keccak::{Hasher, Keccak};
+
+pub fn result_commitment(result: i128, nonce: &[u8; 32]) -> [u8; 32] {
+    let mut keccak = Keccak::v256();
+    keccak.update(&result.to_be_bytes());
+    keccak.update(nonce);
+
+    let mut commitment = [0u8; 32];
+    keccak.finalize(&mut commitment);
+    commitment
+}
+
+/// Replaces the committed result with a commitment to it.
+pub fn hide_result(public: &mut PublicValues, nonce: &[u8; 32]) {
+    public.resultCommitment = result_commitment(public.result, nonce).into();
+    public.result = 0;
+}
@@ -0,0 +1,113 @@
+//! Proving which rows differ between two versions of a dataset.
+//!
+//! Rows are compared by position: a row present in both versions whose leaf hash differs is
+//! modified, and rows past the end of the shorter version are added or removed. The diff program
+//! commits both roots along with a digest of the changed rows, which a reviewer recomputes from
+//! the claimed list of changes.
+
+use crate::{hash_row, parse_csv, MerkleFrontier};
+use alloy_sol_types::sol;
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    struct DiffValues {
+        bytes32 oldRoot;
+        bytes32 newRoot;
+        uint64 oldRows;
+        uint64 newRows;
+        uint64 added;
+        uint64 removed;
+        uint64 modified;
+        bytes32 changesDigest;
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiffInput {
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RowChange {
+    pub row: u64,
+    pub kind: ChangeKind,
+}
+
+/// Computes what the diff program commits, along with the changed rows in row order.
+/// An empty version has a zero root.
+pub fn diff(input: DiffInput) -> (DiffValues, Vec<RowChange>) {
+    let old = parse_csv(input.old, None);
+    let new = parse_csv(input.new, None);
+
+    let mut old_frontier = MerkleFrontier::new();
+    let mut new_frontier = MerkleFrontier::new();
+    let mut changes = Vec::new();
+    let (mut old_rows, mut new_rows) = (old.rows(), new.rows());
+    loop {
+        let row = old_frontier.leaves().max(new_frontier.leaves());
+        let kind = match (old_rows.next(), new_rows.next()) {
+            (Some(old_row), Some(new_row)) => {
+                let (old_leaf, new_leaf) = (hash_row(old_row), hash_row(new_row));
+                old_frontier.push(old_leaf);
+                new_frontier.push(new_leaf);
+                (old_leaf != new_leaf).then_some(ChangeKind::Modified)
+            }
+            (Some(old_row), None) => {
+                old_frontier.push(hash_row(old_row));
+                Some(ChangeKind::Removed)
+            }
+            (None, Some(new_row)) => {
+                new_frontier.push(hash_row(new_row));
+                Some(ChangeKind::Added)
+            }
+            (None, None) => break,
+        };
+        if let Some(kind) = kind {
+            changes.push(RowChange { row, kind });
+        }
+    }
+
+    let count = |kind| changes.iter().filter(|c| c.kind == kind).count() as u64;
+    let values = DiffValues {
+        oldRoot: old_frontier.root().unwrap_or_default().into(),
+        newRoot: new_frontier.root().unwrap_or_default().into(),
+        oldRows: old_frontier.leaves(),
+        newRows: new_frontier.leaves(),
+        added: count(ChangeKind::Added),
+        removed: count(ChangeKind::Removed),
+        modified: count(ChangeKind::Modified),
+        changesDigest: changes_digest(&changes).into(),
+    };
+
+    (values, changes)
+}
+
+/// Hashes each change as its kind (1 added, 2 removed, 3 modified) followed by the big-endian
+/// row index.
+pub fn changes_digest(changes: &[RowChange]) -> [u8; 32] {
+    let mut keccak = Keccak::v256();
+    for change in changes {
+        let kind: u8 = match change.kind {
+            ChangeKind::Added => 1,
+            ChangeKind::Removed => 2,
+            ChangeKind::Modified => 3,
+        };
+        keccak.update(&[kind]);
+        keccak.update(&change.row.to_be_bytes());
+    }
+
+    let mut digest = [0u8; 32];
+    keccak.finalize(&mut digest);
+    digest
+}
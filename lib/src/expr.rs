@@ -0,0 +1,354 @@
+//! Computed columns: an expression evaluated for every row before it is aggregated, such as
+//! `price * qty - discount` or `if(region == "EU", price * 1.2, price)`.
+//!
+//! Values are fixed-point integers. Every node has a static scale, the number of decimal digits
+//! its value carries, so every row of an expression yields a value at the same scale and the
+//! aggregate can commit it in `decimal_points`. The program commits the keccak hash of the
+//! expression's canonical form, which is what [`Expr`]'s `Display` prints.
+
+use crate::{hash, mean, median, parse_i128, Comparator, Csv, Op};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// The fewest decimal digits a quotient is computed to.
+pub const DIV_SCALE: u32 = 3;
+
+/// The most decimal digits any value may carry, so rescaling stays within `i128`.
+const MAX_SCALE: u32 = 18;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Expr {
+    /// The cell of the named column, as an integer, or as text when compared with text.
+    Col(String),
+    /// The number `units / 10^scale`.
+    Num {
+        units: i128,
+        scale: u32,
+    },
+    /// Text, which can only be compared with a column for equality.
+    Str(String),
+    Neg(Box<Expr>),
+    Bin(BinOp, Box<Expr>, Box<Expr>),
+    /// 1 if the comparison holds and 0 otherwise.
+    Cmp(Comparator, Box<Expr>, Box<Expr>),
+    /// 1 if both are non-zero and 0 otherwise.
+    And(Box<Expr>, Box<Expr>),
+    /// 1 if either is non-zero and 0 otherwise.
+    Or(Box<Expr>, Box<Expr>),
+    /// 1 if the expression is zero and 0 otherwise.
+    Not(Box<Expr>),
+    /// The second expression if the first is non-zero, otherwise the third.
+    If(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    /// Rounded half away from zero to at least [`DIV_SCALE`] decimal digits.
+    Div,
+}
+
+impl Expr {
+    /// The number of decimal digits the expression's values carry.
+    pub fn scale(&self) -> u32 {
+        let scale = match self {
+            Expr::Col(_)
+            | Expr::Str(_)
+            | Expr::Cmp(..)
+            | Expr::And(..)
+            | Expr::Or(..)
+            | Expr::Not(_) => 0,
+            Expr::Num { scale, .. } => *scale,
+            Expr::Neg(expr) => expr.scale(),
+            Expr::Bin(BinOp::Add | BinOp::Sub, left, right) => left.scale().max(right.scale()),
+            Expr::Bin(BinOp::Mul, left, right) => left.scale() + right.scale(),
+            Expr::Bin(BinOp::Div, left, _) => left.scale().max(DIV_SCALE),
+            Expr::If(_, then, otherwise) => then.scale().max(otherwise.scale()),
+        };
+        assert!(
+            scale <= MAX_SCALE,
+            "expression has more than {MAX_SCALE} decimals"
+        );
+        scale
+    }
+
+    /// The hash committed as `exprHash`.
+    pub fn hash(&self) -> [u8; 32] {
+        hash(self.to_string().as_bytes())
+    }
+
+    /// The distinct columns the expression reads.
+    pub fn columns(&self) -> Vec<&str> {
+        let mut columns = Vec::new();
+        self.visit_columns(&mut columns);
+        columns
+    }
+
+    fn visit_columns<'a>(&'a self, columns: &mut Vec<&'a str>) {
+        match self {
+            Expr::Col(col) if !columns.contains(&col.as_str()) => columns.push(col),
+            Expr::Col(_) | Expr::Num { .. } | Expr::Str(_) => {}
+            Expr::Neg(expr) | Expr::Not(expr) => expr.visit_columns(columns),
+            Expr::Bin(_, left, right)
+            | Expr::Cmp(_, left, right)
+            | Expr::And(left, right)
+            | Expr::Or(left, right) => {
+                left.visit_columns(columns);
+                right.visit_columns(columns);
+            }
+            Expr::If(cond, then, otherwise) => {
+                cond.visit_columns(columns);
+                then.visit_columns(columns);
+                otherwise.visit_columns(columns);
+            }
+        }
+    }
+
+    /// Resolves the expression against the file's header, once rather than for every row.
+    /// Panics if the file lacks a column it reads or it has more than 18 decimals.
+    pub(crate) fn resolve<'e>(&'e self, csv: &Csv) -> Resolved<'e> {
+        let resolve = |expr: &'e Expr| Box::new(expr.resolve(csv));
+        let node = match self {
+            Expr::Col(col) => Node::Col(csv.index_of(col)),
+            Expr::Num { units, .. } => Node::Num(*units),
+            Expr::Str(s) => Node::Str(s),
+            Expr::Neg(expr) => Node::Neg(resolve(expr)),
+            Expr::Bin(op, left, right) => Node::Bin(*op, resolve(left), resolve(right)),
+            Expr::Cmp(comparator, left, right) => {
+                Node::Cmp(*comparator, resolve(left), resolve(right))
+            }
+            Expr::And(left, right) => Node::And(resolve(left), resolve(right)),
+            Expr::Or(left, right) => Node::Or(resolve(left), resolve(right)),
+            Expr::Not(expr) => Node::Not(resolve(expr)),
+            Expr::If(cond, then, otherwise) => {
+                Node::If(resolve(cond), resolve(then), resolve(otherwise))
+            }
+        };
+        Resolved {
+            scale: self.scale(),
+            node,
+        }
+    }
+}
+
+/// An expression resolved against a file's header: each column is replaced by its index and
+/// each node carries its scale, so evaluating a row neither looks up columns nor walks the tree
+/// for scales.
+pub(crate) struct Resolved<'a> {
+    pub(crate) scale: u32,
+    node: Node<'a>,
+}
+
+enum Node<'a> {
+    Col(usize),
+    Num(i128),
+    Str(&'a str),
+    Neg(Box<Resolved<'a>>),
+    Bin(BinOp, Box<Resolved<'a>>, Box<Resolved<'a>>),
+    Cmp(Comparator, Box<Resolved<'a>>, Box<Resolved<'a>>),
+    And(Box<Resolved<'a>>, Box<Resolved<'a>>),
+    Or(Box<Resolved<'a>>, Box<Resolved<'a>>),
+    Not(Box<Resolved<'a>>),
+    If(Box<Resolved<'a>>, Box<Resolved<'a>>, Box<Resolved<'a>>),
+}
+
+impl<'a> Resolved<'a> {
+    /// Evaluates the expression for a row's cells. The value is scaled by [`Expr::scale`].
+    pub(crate) fn eval(&self, cells: &[&str]) -> i128 {
+        match &self.node {
+            Node::Col(idx) => parse_i128(cell(cells, *idx)),
+            Node::Num(units) => *units,
+            Node::Str(s) => panic!("text {s:?} is not a number"),
+            Node::Neg(expr) => expr.eval(cells).checked_neg().expect("expression overflow"),
+            Node::Bin(op, left, right) => {
+                let (a, b) = (left.eval(cells), right.eval(cells));
+                match op {
+                    BinOp::Add | BinOp::Sub => {
+                        let (a, b) = (left.rescale(a, self.scale), right.rescale(b, self.scale));
+                        match op {
+                            BinOp::Add => a.checked_add(b),
+                            _ => a.checked_sub(b),
+                        }
+                        .expect("expression overflow")
+                    }
+                    BinOp::Mul => a.checked_mul(b).expect("expression overflow"),
+                    BinOp::Div => {
+                        assert!(b != 0, "division by zero");
+                        // a / 10^scale_a ÷ b / 10^scale_b, scaled to the quotient's scale.
+                        let a = left.rescale(a, self.scale + right.scale);
+                        div_round(a, b)
+                    }
+                }
+            }
+            Node::Cmp(comparator, left, right) => {
+                if matches!(left.node, Node::Str(_)) || matches!(right.node, Node::Str(_)) {
+                    let (a, b) = (left.text(cells), right.text(cells));
+                    return match comparator {
+                        Comparator::Eq => (a == b) as i128,
+                        Comparator::Ne => (a != b) as i128,
+                        _ => panic!("text can only be compared with == and !="),
+                    };
+                }
+                let scale = left.scale.max(right.scale);
+                let a = left.rescale(left.eval(cells), scale);
+                let b = right.rescale(right.eval(cells), scale);
+                comparator.holds(a, b) as i128
+            }
+            Node::And(left, right) => (left.eval(cells) != 0 && right.eval(cells) != 0) as i128,
+            Node::Or(left, right) => (left.eval(cells) != 0 || right.eval(cells) != 0) as i128,
+            Node::Not(expr) => (expr.eval(cells) == 0) as i128,
+            Node::If(cond, then, otherwise) => {
+                let branch = if cond.eval(cells) != 0 {
+                    then
+                } else {
+                    otherwise
+                };
+                branch.rescale(branch.eval(cells), self.scale)
+            }
+        }
+    }
+
+    /// The text of one side of a comparison with text.
+    fn text<'c>(&'c self, cells: &[&'c str]) -> &'c str {
+        match &self.node {
+            Node::Str(s) => s,
+            Node::Col(idx) => cell(cells, *idx),
+            _ => panic!("only columns can be compared with text"),
+        }
+    }
+
+    /// One of this expression's values, rescaled to `to` decimals.
+    fn rescale(&self, value: i128, to: u32) -> i128 {
+        rescale(value, self.scale, to)
+    }
+}
+
+/// The cell at a column's index among a row's cells.
+pub(crate) fn cell<'c>(cells: &[&'c str], idx: usize) -> &'c str {
+    cells.get(idx).expect("row is missing the column")
+}
+
+fn rescale(value: i128, from: u32, to: u32) -> i128 {
+    value
+        .checked_mul(10_i128.pow(to - from))
+        .expect("expression overflow")
+}
+
+/// `a / b` rounded half away from zero.
+fn div_round(a: i128, b: i128) -> i128 {
+    let (quotient, remainder) = (a / b, a % b);
+    if remainder.unsigned_abs() >= b.unsigned_abs() - remainder.unsigned_abs() {
+        quotient + a.signum() * b.signum()
+    } else {
+        quotient
+    }
+}
+
+/// Aggregates the expression's value for every row. Returns the row count, the result and its
+/// decimal points: the expression's scale, plus the op's own decimal points.
+pub fn expr_col(csv: &Csv, expr: &Expr, op: &Op) -> (u64, i128, u16) {
+    let expr = expr.resolve(csv);
+    let scale = expr.scale as u16;
+
+    let values = (0..csv.len()).map(|row| {
+        let cells: Vec<&str> = csv.cells(row).collect();
+        expr.eval(&cells)
+    });
+
+    let (n_rows, result, decimal_points) = match op {
+        Op::Sum | Op::Mean => {
+            let (mut n_rows, mut sum) = (0, 0i128);
+            for value in values {
+                n_rows += 1;
+                sum = sum.checked_add(value).expect("sum overflow");
+            }
+            match op {
+                Op::Mean => mean(n_rows, sum),
+                _ => (n_rows, sum, 0),
+            }
+        }
+        Op::Median => {
+            let mut values: Vec<i128> = values.collect();
+            values.sort();
+            median(&values)
+        }
+        _ => panic!("expressions can only be summed, averaged or have their median taken"),
+    };
+
+    (n_rows, result, decimal_points + scale)
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Col(col) if is_identifier(col) => write!(f, "{col}"),
+            // Backticks inside the name are doubled, so the canonical form stays unambiguous.
+            Expr::Col(col) => write!(f, "`{}`", col.replace('`', "``")),
+            Expr::Num { units, scale } => {
+                let digits = units.unsigned_abs().to_string();
+                let sign = if *units < 0 { "-" } else { "" };
+                let scale = *scale as usize;
+                if scale == 0 {
+                    write!(f, "{sign}{digits}")
+                } else {
+                    let digits = format!("{digits:0>width$}", width = scale + 1);
+                    let (int, frac) = digits.split_at(digits.len() - scale);
+                    write!(f, "{sign}{int}.{frac}")
+                }
+            }
+            Expr::Str(s) => write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+            Expr::Neg(expr) => write!(f, "(-{expr})"),
+            Expr::Bin(op, left, right) => {
+                let op = match op {
+                    BinOp::Add => "+",
+                    BinOp::Sub => "-",
+                    BinOp::Mul => "*",
+                    BinOp::Div => "/",
+                };
+                write!(f, "({left} {op} {right})")
+            }
+            Expr::Cmp(comparator, left, right) => {
+                write!(f, "({left} {} {right})", comparator_symbol(*comparator))
+            }
+            Expr::And(left, right) => write!(f, "({left} and {right})"),
+            Expr::Or(left, right) => write!(f, "({left} or {right})"),
+            Expr::Not(expr) => write!(f, "(not {expr})"),
+            Expr::If(cond, then, otherwise) => write!(f, "if({cond}, {then}, {otherwise})"),
+        }
+    }
+}
+
+/// The symbol a comparator is written with in expressions.
+pub fn comparator_symbol(comparator: Comparator) -> &'static str {
+    match comparator {
+        Comparator::Gt => ">",
+        Comparator::Ge => ">=",
+        Comparator::Lt => "<",
+        Comparator::Le => "<=",
+        Comparator::Eq => "==",
+        Comparator::Ne => "!=",
+    }
+}
+
+/// Words that cannot be column names without backticks, in expressions or queries.
+const KEYWORDS: [&str; 11] = [
+    "if", "and", "or", "not", "select", "from", "join", "on", "where", "group", "by",
+];
+
+/// Whether a column name can be written without backticks, including a column qualified by its
+/// table such as `orders.amount`.
+pub fn is_identifier(name: &str) -> bool {
+    name.split('.').all(|part| {
+        let mut chars = part.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }) && !KEYWORDS
+        .iter()
+        .any(|keyword| name.eq_ignore_ascii_case(keyword))
+}
@@ -0,0 +1,57 @@
+//! Publishing a column's distribution as bucket counts.
+//!
+//! Bucket `i` holds the values in `edges[i]..edges[i + 1]`, and the last bucket also holds the
+//! top edge itself. The vcsv program commits [`HistogramValues`] after the usual
+//! [`PublicValues`](crate::PublicValues), whose `result` counts the values outside every bucket.
+
+use crate::{parse_i128, Csv};
+use alloy_sol_types::sol;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    struct HistogramValues {
+        int128[] edges;
+        uint64[] counts;
+    }
+}
+
+/// Counts the column's values per bucket. Returns the row count, the number of values outside
+/// every bucket and the histogram.
+pub fn histogram(csv: &Csv, edges: Vec<i128>) -> (u64, i128, HistogramValues) {
+    assert!(edges.len() >= 2, "a histogram needs at least two edges");
+    assert!(
+        edges.windows(2).all(|pair| pair[0] < pair[1]),
+        "histogram edges must be increasing"
+    );
+
+    let last = edges.len() - 1;
+    let mut counts = vec![0u64; last];
+    let mut n_rows: u64 = 0;
+    let mut outside: i128 = 0;
+    for val_str in csv.column() {
+        n_rows += 1;
+        let value = parse_i128(val_str);
+        if value < edges[0] || value > edges[last] {
+            outside += 1;
+            continue;
+        }
+        // The number of edges at or below the value, less one, is its bucket.
+        let bucket = edges.partition_point(|edge| *edge <= value) - 1;
+        counts[bucket.min(last - 1)] += 1;
+    }
+
+    (n_rows, outside, HistogramValues { edges, counts })
+}
+
+/// The edges of `buckets` equal-width buckets covering `lower..=upper`, rounding widths down.
+pub fn equal_width_edges(lower: i128, upper: i128, buckets: u32) -> Vec<i128> {
+    let width = upper.checked_sub(lower).expect("range overflow");
+    assert!(
+        buckets > 0 && width >= buckets as i128,
+        "range is too narrow for {buckets} buckets"
+    );
+
+    (0..=buckets as i128)
+        .map(|i| lower + width.checked_mul(i).expect("range overflow") / buckets as i128)
+        .collect()
+}
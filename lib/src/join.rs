@@ -0,0 +1,78 @@
+//! Equi-joins of the proven file with other CSV files, for queries across datasets such as
+//! `SELECT tier, SUM(amount) FROM orders JOIN customers ON customer_id = id GROUP BY tier`.
+//!
+//! A join pairs every row of the table so far with each row of the joined file whose key cell is
+//! equal, dropping rows without a match. The joined table's columns are qualified by their table,
+//! as in `orders.amount`, and can be named alone when no other table has them. The program
+//! commits the Merkle root of every joined file in [`QueryValues`](crate::QueryValues), while the
+//! tables and keys are part of the query's canonical form and so of its `planHash`.
+
+use crate::{merkelize, parse_csv, Csv, Query};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Join {
+    /// The name the query gives the joined file.
+    pub table: String,
+    /// The key column of the table joined so far.
+    pub left: String,
+    /// The key column of the joined file, bare or qualified by `table`.
+    pub right: String,
+}
+
+/// Joins the proven file with `files`, one per `JOIN` of the query, in order. Returns the joined
+/// table and the Merkle root of each file.
+pub fn join(csv: &Csv, query: &Query, files: Vec<Vec<u8>>) -> (Csv, Vec<[u8; 32]>) {
+    assert_eq!(
+        files.len(),
+        query.joins.len(),
+        "the query joins {} files but {} were given",
+        query.joins.len(),
+        files.len()
+    );
+
+    let mut cols: Vec<String> = qualify(&query.from, &csv.cols).collect();
+    let mut rows: Vec<String> = csv.rows().map(str::to_string).collect();
+    let mut roots = Vec::with_capacity(files.len());
+    for (join, file) in query.joins.iter().zip(files) {
+        let other = parse_csv(file, None);
+        roots.push(merkelize(&other));
+
+        let right = join
+            .right
+            .strip_prefix(&format!("{}.", join.table))
+            .unwrap_or(&join.right);
+        let right = other.index_of(right);
+        let mut matches: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (row, key) in other.column_at(right).enumerate() {
+            matches.entry(key).or_default().push(row);
+        }
+
+        let so_far = table(&cols, &rows);
+        let left = so_far.index_of(&join.left);
+        let mut joined = Vec::new();
+        for (row, key) in so_far.column_at(left).enumerate() {
+            for &other_row in matches.get(key).into_iter().flatten() {
+                joined.push(format!("{},{}", so_far.row(row), other.row(other_row)));
+            }
+        }
+        cols.extend(qualify(&join.table, &other.cols));
+        rows = joined;
+    }
+    (table(&cols, &rows), roots)
+}
+
+/// A table of already joined rows.
+fn table(cols: &[String], rows: &[String]) -> Csv {
+    let mut data = cols.join(",");
+    for row in rows {
+        data.push('\n');
+        data.push_str(row);
+    }
+    parse_csv(data.into_bytes(), None)
+}
+
+fn qualify<'a>(table: &'a str, cols: &'a [String]) -> impl Iterator<Item = String> + 'a {
+    cols.iter().map(move |col| format!("{table}.{col}"))
+}
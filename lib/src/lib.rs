@@ -1,43 +1,237 @@
-use alloy_sol_types::sol;
+mod append;
+mod chunk;
+mod claim;
+mod commitment;
+mod diff;
+mod expr;
+mod histogram;
+mod join;
+mod noise;
+mod pairwise;
+mod query;
+mod signature;
+mod top_k;
+
+pub use append::{append, AppendInput, AppendValues, ProgramKeys};
+pub use chunk::{
+    aggregate, chunk_values, median_pivots, split_chunks, vkey_bytes, AggregateInput, ChunkInput,
+    ChunkValues,
+};
+pub use claim::{comparator_to_u8, Claim, Comparator};
+pub use commitment::{hide_result, result_commitment};
+pub use diff::{changes_digest, diff, ChangeKind, DiffInput, DiffValues, RowChange};
+pub use expr::{comparator_symbol, expr_col, is_identifier, BinOp, Expr, DIV_SCALE};
+pub use histogram::{equal_width_edges, histogram, HistogramValues};
+pub use join::{join, Join};
+pub use noise::Noise;
+pub use pairwise::pairwise_col;
+pub use query::{query, Aggregate, Query, QueryRow, QueryValues, Selected};
+pub use signature::{address, eip191_hash, recover_signer, signer};
+pub use top_k::{top_k, TopKValues};
+
+use alloy_sol_types::{sol, SolType};
 use clap::ValueEnum;
+use core::ops::Range;
 use serde::{Deserialize, Serialize};
 use tiny_keccak::{Hasher, Keccak};
 
-#[derive(ValueEnum, Clone, Debug, Serialize, Deserialize)]
+#[derive(ValueEnum, Clone, Debug, Default, Serialize, Deserialize)]
 pub enum Op {
+    #[default]
     Sum,
     Mean,
     Median,
+    /// Counts the values outside the input's bounds.
+    RangeCheck,
+    /// Counts the values in each bucket between the input's edges.
+    Histogram,
+    /// The population covariance of the selected column and the input's `y_col`.
+    Covariance,
+    /// The Pearson correlation of the selected column and the input's `y_col`.
+    Correlation,
+    /// The least squares slope of the input's `y_col` against the selected column.
+    Slope,
+    /// The least squares intercept of the input's `y_col` against the selected column.
+    Intercept,
+    /// The mean of the selected column weighted by the input's `y_col`.
+    WeightedMean,
+    /// The sum of the selected column times the input's `y_col`, row by row.
+    SumProduct,
+    /// The sum of the input's `k` largest values.
+    TopK,
+    /// The sum of the input's `k` smallest values.
+    BottomK,
+    /// Runs the input's query, counting the groups it returns.
+    #[value(skip)]
+    Query,
+}
+
+impl Op {
+    /// Whether the op is a statistic of two columns, which needs the input's `y_col`.
+    pub fn is_pairwise(&self) -> bool {
+        matches!(
+            self,
+            Op::Covariance
+                | Op::Correlation
+                | Op::Slope
+                | Op::Intercept
+                | Op::WeightedMean
+                | Op::SumProduct
+        )
+    }
 }
 
 #[derive(ValueEnum, Clone, Debug, Serialize, Deserialize)]
 pub enum Backend {
     Cpu,
     Network,
+    Mock,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofType {
+    Core,
+    Compressed,
+    Groth16,
+    Plonk,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Input {
     pub csv: Vec<u8>,
     pub col: String,
     pub op: Op,
+    /// The data provider's 65-byte EIP-191 signature over the file root, if any.
+    #[serde(default)]
+    pub signature: Option<Vec<u8>>,
+    /// Publish a differentially private sum or mean instead of the exact one.
+    #[serde(default)]
+    pub noise: Option<Noise>,
+    /// The fewest rows a result may be computed over. Smaller cohorts fail to prove.
+    #[serde(default)]
+    pub min_rows: u64,
+    /// The inclusive `(lower, upper)` bounds [`Op::RangeCheck`] checks every value against.
+    #[serde(default)]
+    pub bounds: Option<(i128, i128)>,
+    /// Commit only whether the result satisfies this claim, instead of the result.
+    #[serde(default)]
+    pub claim: Option<Claim>,
+    /// Commit `keccak(result || nonce)` instead of the result, to reveal it later.
+    #[serde(default)]
+    pub result_nonce: Option<[u8; 32]>,
+    /// The increasing bucket edges of [`Op::Histogram`].
+    #[serde(default)]
+    pub edges: Vec<i128>,
+    /// The second column of a pairwise statistic, where the selected column is `x`, or the
+    /// weights of [`Op::WeightedMean`] and [`Op::SumProduct`].
+    #[serde(default)]
+    pub y_col: Option<String>,
+    /// Aggregate this expression's value for every row instead of the `col` column.
+    #[serde(default)]
+    pub expr: Option<Expr>,
+    /// The query [`Op::Query`] runs.
+    #[serde(default)]
+    pub query: Option<Query>,
+    /// The files the query joins, one per `JOIN`, in order.
+    #[serde(default)]
+    pub joined: Vec<Vec<u8>>,
+    /// How many values [`Op::TopK`] and [`Op::BottomK`] select.
+    #[serde(default)]
+    pub k: u64,
+    /// Commit the rows [`Op::TopK`] and [`Op::BottomK`] select, not just their sum.
+    #[serde(default)]
+    pub reveal_rows: bool,
 }
 
+/// A parsed CSV that borrows every row from the original buffer instead of copying its cells.
 pub struct Csv {
-    pub lines: Vec<Vec<String>>,
-    pub headers: String,
+    data: String,
+    /// Byte range of each non-empty data row in `data`, without its trailing `\n`.
+    rows: Vec<Range<usize>>,
     pub cols: Vec<String>,
     pub idx: usize,
 }
 
+impl Csv {
+    /// Number of data rows.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn headers(&self) -> &str {
+        self.data.split('\n').next().unwrap_or_default()
+    }
+
+    /// The row as it appears in the file.
+    pub fn row(&self, row: usize) -> &str {
+        &self.data[self.rows[row].clone()]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &str> {
+        self.rows.iter().map(|range| &self.data[range.clone()])
+    }
+
+    /// The trimmed cells of a row.
+    pub fn cells(&self, row: usize) -> impl Iterator<Item = &str> {
+        self.row(row).split(',').map(trim_ascii)
+    }
+
+    /// The trimmed values of the selected column, one per row.
+    pub fn column(&self) -> impl Iterator<Item = &str> {
+        self.column_at(self.idx)
+    }
+
+    /// The trimmed values of the column at `idx`, one per row.
+    pub fn column_at(&self, idx: usize) -> impl Iterator<Item = &str> {
+        self.rows()
+            .map(move |row| trim_ascii(row.split(',').nth(idx).expect("row is missing the column")))
+    }
+
+    /// The index of the column named `col`. In a joined table, where every column is qualified
+    /// by its table, a column can also be named alone when no other table has it.
+    pub fn index_of(&self, col: &str) -> usize {
+        if let Some(idx) = self.cols.iter().position(|name| name == col) {
+            return idx;
+        }
+        let suffix = format!(".{col}");
+        let mut qualified = (0..self.cols.len()).filter(|&idx| self.cols[idx].ends_with(&suffix));
+        match (qualified.next(), qualified.next()) {
+            (Some(idx), None) => idx,
+            (Some(_), Some(_)) => panic!("column {col} is ambiguous"),
+            (None, _) => panic!("column not found"),
+        }
+    }
+}
+
 sol! {
+    #[derive(Debug, Default, PartialEq, Eq)]
     struct PublicValues {
         bytes32 fileRoot;
+        address signer;
         uint8 op;
         bytes32 colHash;
+        bytes32 yColHash;
+        bytes32 exprHash;
         uint64 n_rows;
+        uint64 min_rows;
+        uint64 k;
         int128 result;
         uint16 decimal_points;
+        /// The bounds of a range check, which counts the values outside them, or of a noised
+        /// sum or mean, which requires every value within them. Range checks cannot be noised.
+        int128 lower;
+        int128 upper;
+        uint8 comparator;
+        int128 threshold;
+        bytes32 resultCommitment;
+        bytes32 seedHash;
+        uint32 epsilon_millis;
+        int128 sensitivity;
     }
 }
 
@@ -45,12 +239,8 @@ pub fn sum_col(csv: &Csv) -> (u64, i128, u16) {
     let mut n_rows: u64 = 0;
     let mut sum: i128 = 0;
 
-    for line in &csv.lines {
-        if line.is_empty() {
-            continue;
-        }
+    for val_str in csv.column() {
         n_rows += 1;
-        let val_str = &line[csv.idx];
         let v: i128 = parse_i128(val_str);
         sum = sum.checked_add(v).expect("sum overflow");
     }
@@ -61,11 +251,16 @@ pub fn sum_col(csv: &Csv) -> (u64, i128, u16) {
 pub fn mean_col(csv: &Csv) -> (u64, i128, u16) {
     let (n_rows, sum, _) = sum_col(csv);
 
+    mean(n_rows, sum)
+}
+
+/// The mean of `n_rows` values adding up to `sum`, scaled to 3 decimal points.
+pub fn mean(n_rows: u64, sum: i128) -> (u64, i128, u16) {
     if n_rows == 0 {
         return (0, 0, 0); // TODO: handle error better
     }
 
-    let decimal: u16 = 3;
+    let decimal = decimal_points(&Op::Mean);
     let multiplier = 10_i128.pow(decimal as u32);
     let mean_float = (sum as f64 * multiplier as f64) / (n_rows as f64);
     let mean_scaled = mean_float.round() as i128;
@@ -74,30 +269,232 @@ pub fn mean_col(csv: &Csv) -> (u64, i128, u16) {
 }
 
 pub fn median_col(csv: &Csv) -> (u64, i128, u16) {
-    let mut elements = Vec::new();
-    for line in &csv.lines {
-        if line.is_empty() {
-            continue;
+    let mut elements: Vec<i128> = csv.column().map(parse_i128).collect();
+    elements.sort();
+
+    median(&elements)
+}
+
+/// The median of already sorted values, scaled to 3 decimal points.
+pub fn median(elements: &[i128]) -> (u64, i128, u16) {
+    assert!(!elements.is_empty(), "no elements");
+
+    let n = elements.len();
+    median_of_middle(n as u64, elements[(n - 1) / 2], elements[n / 2])
+}
+
+/// The median of `n_rows` values whose middle values, at ranks `(n_rows - 1) / 2` and
+/// `n_rows / 2`, are `low` and `high`, scaled to 3 decimal points.
+pub fn median_of_middle(n_rows: u64, low: i128, high: i128) -> (u64, i128, u16) {
+    let median = (low as f64 + high as f64) / 2.0;
+
+    let decimal = decimal_points(&Op::Median);
+    let multiplier = 10_f64.powi(decimal as i32);
+    let median_scaled = (median * multiplier).round() as i128;
+
+    (n_rows, median_scaled, decimal)
+}
+
+/// Counts the values outside `lower..=upper`. A result of zero proves every value is in range.
+pub fn range_check_col(csv: &Csv, lower: i128, upper: i128) -> (u64, i128, u16) {
+    assert!(lower <= upper, "range bounds are empty");
+
+    let mut n_rows: u64 = 0;
+    let mut violations: i128 = 0;
+    for val_str in csv.column() {
+        n_rows += 1;
+        if !(lower..=upper).contains(&parse_i128(val_str)) {
+            violations += 1;
         }
-        let v: i128 = parse_i128(&line[csv.idx]);
-        elements.push(v);
     }
 
-    assert!(elements.len() != 0, "no elements");
-    elements.sort();
+    (n_rows, violations, 0)
+}
 
-    let mid = elements.len() / 2;
-    let median = if elements.len() % 2 == 1 {
-        elements[mid] as f64
-    } else {
-        (elements[mid - 1] as f64 + elements[mid] as f64) / 2.0
+/// What the vcsv program commits after the [`PublicValues`] of ops with more than one result.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Table {
+    Histogram(HistogramValues),
+    Query(QueryValues),
+    TopK(TopKValues),
+}
+
+impl Table {
+    pub fn abi_encode(&self) -> Vec<u8> {
+        match self {
+            Table::Histogram(histogram) => HistogramValues::abi_encode(histogram),
+            Table::Query(query) => QueryValues::abi_encode(query),
+            Table::TopK(top_k) => TopKValues::abi_encode(top_k),
+        }
+    }
+}
+
+/// Computes the [`PublicValues`] the vcsv program commits for an input.
+pub fn run(input: Input) -> PublicValues {
+    run_with_table(input).0
+}
+
+/// Computes everything the vcsv program commits for an input: the [`PublicValues`], followed
+/// by the histogram of [`Op::Histogram`], the result table of [`Op::Query`] or the revealed rows
+/// of [`Op::TopK`] and [`Op::BottomK`].
+pub fn run_with_table(input: Input) -> (PublicValues, Option<Table>) {
+    let Input {
+        csv,
+        col,
+        op,
+        signature,
+        noise,
+        min_rows,
+        bounds,
+        claim,
+        result_nonce,
+        edges,
+        y_col,
+        expr,
+        query: plan,
+        joined,
+        k,
+        reveal_rows,
+    } = input;
+    assert!(
+        joined.is_empty() || plan.is_some(),
+        "only queries can join files"
+    );
+
+    // Expressions and queries read their own columns, so `col` is not selected or committed.
+    let reads_col = expr.is_none() && plan.is_none();
+    let csv_cont = parse_csv(csv, reads_col.then_some(col.as_str()));
+    let file_root = merkelize(&csv_cont);
+    let col_hash = match reads_col {
+        true => hash(col.as_bytes()),
+        false => [0; 32],
+    };
+    let op_u8 = op_to_u8(op.clone());
+
+    let mut table = None;
+    let (n_rows, result, decimal_points) = match (&op, &noise, &expr) {
+        (_, Some(_), Some(_)) => panic!("expressions cannot be noised"),
+        (op, None, Some(expr)) => expr_col(&csv_cont, expr, op),
+        (Op::Sum, None, None) => sum_col(&csv_cont),
+        (Op::Mean, None, None) => mean_col(&csv_cont),
+        (Op::Median, None, None) => median_col(&csv_cont),
+        (Op::RangeCheck, None, None) => {
+            let (lower, upper) = bounds.expect("range check needs bounds");
+            range_check_col(&csv_cont, lower, upper)
+        }
+        (Op::Histogram, None, None) => {
+            let (n_rows, outside, values) = histogram(&csv_cont, edges);
+            table = Some(Table::Histogram(values));
+            (n_rows, outside, 0)
+        }
+        (Op::Query, None, None) => {
+            let plan = plan.expect("query op needs a query");
+            assert_eq!(
+                joined.len(),
+                plan.joins.len(),
+                "the query joins {} files but {} were given",
+                plan.joins.len(),
+                joined.len()
+            );
+            let (n_rows, values) = match plan.joins.is_empty() {
+                true => query(&csv_cont, &plan, min_rows),
+                false => {
+                    let (joined, roots) = join(&csv_cont, &plan, joined);
+                    let (n_rows, values) = query(&joined, &plan, min_rows);
+                    let values = QueryValues {
+                        joinRoots: roots.into_iter().map(Into::into).collect(),
+                        ..values
+                    };
+                    (n_rows, values)
+                }
+            };
+            let groups = values.rows.len() as i128;
+            table = Some(Table::Query(values));
+            (n_rows, groups, 0)
+        }
+        (Op::TopK | Op::BottomK, None, None) => {
+            let (n_rows, sum, values) = top_k(&csv_cont, k, &op, reveal_rows);
+            // The sum, or the revealed rows, only cover the k selected rows.
+            check_cohort(k, min_rows);
+            table = values.map(Table::TopK);
+            (n_rows, sum, 0)
+        }
+        (Op::Query, Some(_), None) => panic!("queries cannot be noised"),
+        (Op::TopK | Op::BottomK, Some(_), None) => panic!("top-k cannot be noised"),
+        (Op::Median, Some(_), None) => panic!("median cannot be noised"),
+        (Op::RangeCheck, Some(_), None) => panic!("range checks cannot be noised"),
+        (Op::Histogram, Some(_), None) => panic!("histograms cannot be noised"),
+        (op, Some(_), None) if op.is_pairwise() => panic!("pairwise statistics cannot be noised"),
+        (_, Some(noise), None) => {
+            for value in csv_cont.column() {
+                noise.check_bounds(parse_i128(value));
+            }
+            let (n_rows, sum, _) = sum_col(&csv_cont);
+            let sum = sum
+                .checked_add(noise.sample(&file_root, &col_hash, op_u8))
+                .expect("sum overflow");
+            match op {
+                Op::Mean => mean(n_rows, sum),
+                _ => (n_rows, sum, 0),
+            }
+        }
+        (op, None, None) => {
+            let y_col = y_col
+                .as_deref()
+                .expect("pairwise statistics need a y column");
+            pairwise_col(&csv_cont, csv_cont.index_of(y_col), op)
+        }
     };
 
-    let decimal: u16 = 3;
-    let multiplier = 10_f64.powi(decimal as i32);
-    let median_scaled = (median * multiplier).round() as i128;
+    check_cohort(n_rows, min_rows);
+
+    let mut public = PublicValues {
+        fileRoot: file_root.into(),
+        signer: signer(&file_root, signature.as_deref()),
+        op: op_u8,
+        colHash: col_hash.into(),
+        n_rows,
+        min_rows,
+        result,
+        decimal_points,
+        ..Default::default()
+    };
+    if let Some(expr) = &expr {
+        public.exprHash = expr.hash().into();
+    }
+    if let Some(y_col) = y_col.filter(|_| op.is_pairwise()) {
+        public.yColHash = hash(y_col.as_bytes()).into();
+    }
+    if let Op::TopK | Op::BottomK = op {
+        public.k = k;
+    }
+    if let (Op::RangeCheck, Some((lower, upper))) = (op, bounds) {
+        public.lower = lower;
+        public.upper = upper;
+    }
+    if let Some(noise) = noise {
+        public.lower = noise.lower;
+        public.upper = noise.upper;
+        public.seedHash = noise.seed_hash().into();
+        public.epsilon_millis = noise.epsilon_millis;
+        public.sensitivity = noise.sensitivity();
+    }
+    if let Some(claim) = claim {
+        claim.apply(&mut public);
+    }
+    if let Some(nonce) = result_nonce {
+        hide_result(&mut public, &nonce);
+    }
+    (public, table)
+}
 
-    (elements.len() as u64, median_scaled, decimal)
+/// Panics if a result would be computed over fewer than `min_rows` rows, so small cohorts are
+/// never published.
+pub fn check_cohort(n_rows: u64, min_rows: u64) {
+    assert!(
+        n_rows >= min_rows,
+        "cohort of {n_rows} rows is below the minimum of {min_rows}"
+    );
 }
 
 pub fn hash(s: &[u8]) -> [u8; 32] {
@@ -110,10 +507,10 @@ pub fn hash(s: &[u8]) -> [u8; 32] {
 }
 
 pub fn parse_csv(csv: Vec<u8>, col: Option<&str>) -> Csv {
-    let s = core::str::from_utf8(&csv).expect("csv not utf8");
-    let mut lines_iter = s.split("\n");
+    let data = String::from_utf8(csv).expect("csv not utf8");
 
-    let headers = lines_iter.next().expect("empty csv").to_string();
+    let mut lines_iter = data.split('\n');
+    let headers = lines_iter.next().expect("empty csv");
     let cols: Vec<String> = headers
         .split(",")
         .map(trim_ascii)
@@ -128,55 +525,147 @@ pub fn parse_csv(csv: Vec<u8>, col: Option<&str>) -> Csv {
         None => 0,
     };
 
-    // Parse all remaining lines into Vec<Vec<String>>
-    let mut lines = Vec::new();
+    // Record where each remaining line starts and ends; the cells are only split when read.
+    let mut rows = Vec::new();
+    let mut start = headers.len() + 1;
     for line in lines_iter {
-        if line.is_empty() {
-            continue;
+        if !line.is_empty() {
+            rows.push(start..start + line.len());
         }
-        let row: Vec<String> = line
-            .split(",")
-            .map(trim_ascii)
-            .map(|s| s.to_string())
-            .collect();
-        lines.push(row);
+        start += line.len() + 1;
     }
 
     Csv {
-        lines,
-        headers,
+        data,
+        rows,
         cols,
         idx,
     }
 }
 
 pub fn merkelize(csv: &Csv) -> [u8; 32] {
-    let mut hashes: Vec<[u8; 32]> = csv
-        .lines
-        .iter()
-        .map(|line| {
-            let row = line.join(",");
-            hash(row.as_bytes())
-        })
-        .collect();
+    frontier(csv).root().expect("no rows")
+}
+
+/// The Merkle frontier after pushing every row of the CSV.
+pub fn frontier(csv: &Csv) -> MerkleFrontier {
+    let mut frontier = MerkleFrontier::new();
+    for row in csv.rows() {
+        frontier.push(hash_row(row));
+    }
+    frontier
+}
+
+/// Hashes a raw CSV line into its Merkle leaf: every cell trimmed and the cells joined by `,`.
+/// The row is fed to Keccak cell by cell, so nothing is allocated.
+pub fn hash_row(line: &str) -> [u8; 32] {
+    let mut keccak = Keccak::v256();
+    for (i, cell) in line.split(',').enumerate() {
+        if i > 0 {
+            keccak.update(b",");
+        }
+        keccak.update(trim_ascii(cell).as_bytes());
+    }
+
+    let mut hash = [0u8; 32];
+    keccak.finalize(&mut hash);
+    hash
+}
 
-    while hashes.len() > 1 {
-        let pairs = hashes.chunks(2);
-        hashes = pairs
-            .map(|pair| {
-                let left = pair[0];
-                let right = if pair.len() == 2 { pair[1] } else { pair[0] };
+/// Hashes two sibling nodes into their parent.
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(left);
+    buf[32..].copy_from_slice(right);
 
-                let mut buf = [0u8; 64];
-                buf[..32].copy_from_slice(&left);
-                buf[32..].copy_from_slice(&right);
+    hash(&buf)
+}
+
+/// Builds the same tree as [`merkelize`] from leaves pushed one at a time.
+///
+/// Only the rightmost unpaired node of each level is kept, so memory is O(log n) in the number
+/// of leaves. Every node is reported to the `visit` callbacks as `(level, index, hash)` when it
+/// is created, which is enough to collect an inclusion path while streaming.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleFrontier {
+    pending: Vec<Option<[u8; 32]>>,
+    leaves: u64,
+}
+
+impl MerkleFrontier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn leaves(&self) -> u64 {
+        self.leaves
+    }
 
-                hash(&buf)
-            })
-            .collect();
+    pub fn push(&mut self, leaf: [u8; 32]) {
+        self.push_with(leaf, |_, _, _| {});
     }
 
-    hashes[0]
+    pub fn push_with(&mut self, leaf: [u8; 32], mut visit: impl FnMut(u32, u64, &[u8; 32])) {
+        let mut node = leaf;
+        let mut level = 0usize;
+        let mut index = self.leaves;
+        visit(0, index, &node);
+        self.leaves += 1;
+
+        loop {
+            if self.pending.len() == level {
+                self.pending.push(None);
+            }
+            match self.pending[level].take() {
+                None => {
+                    self.pending[level] = Some(node);
+                    return;
+                }
+                Some(left) => {
+                    node = hash_pair(&left, &node);
+                    level += 1;
+                    index /= 2;
+                    visit(level as u32, index, &node);
+                }
+            }
+        }
+    }
+
+    /// Returns the root, or `None` if no leaves were pushed.
+    pub fn root(&self) -> Option<[u8; 32]> {
+        self.root_with(|_, _, _| {})
+    }
+
+    /// Returns the root, reporting the nodes on the right edge that only exist once the
+    /// odd nodes at the end of each level are paired with themselves.
+    pub fn root_with(&self, mut visit: impl FnMut(u32, u64, &[u8; 32])) -> Option<[u8; 32]> {
+        let n = self.leaves;
+        if n == 0 {
+            return None;
+        }
+
+        // The partial node at the end of the current level, built from the leftover leaves.
+        let mut carry: Option<[u8; 32]> = None;
+        let mut level = 0u32;
+        loop {
+            let size = n.div_ceil(1u64 << level);
+            let pending = self.pending.get(level as usize).copied().flatten();
+            if size == 1 {
+                return carry.or(pending);
+            }
+
+            let parent = match (pending, carry) {
+                (Some(left), right) => Some(hash_pair(&left, &right.unwrap_or(left))),
+                (None, Some(last)) => Some(hash_pair(&last, &last)),
+                (None, None) => None,
+            };
+            level += 1;
+            if let Some(parent) = &parent {
+                visit(level, n >> level, parent);
+            }
+            carry = parent;
+        }
+    }
 }
 
 pub fn trim_ascii(s: &str) -> &str {
@@ -197,6 +686,37 @@ pub fn op_to_u8(op: Op) -> u8 {
         Op::Sum => 0,
         Op::Mean => 1,
         Op::Median => 2,
+        Op::RangeCheck => 3,
+        Op::Histogram => 4,
+        Op::Covariance => 5,
+        Op::Correlation => 6,
+        Op::Slope => 7,
+        Op::Intercept => 8,
+        Op::WeightedMean => 9,
+        Op::SumProduct => 10,
+        Op::Query => 11,
+        Op::TopK => 12,
+        Op::BottomK => 13,
+    }
+}
+
+/// The number of decimal points the results of `op` are scaled by.
+pub fn decimal_points(op: &Op) -> u16 {
+    match op {
+        Op::Mean
+        | Op::Median
+        | Op::Covariance
+        | Op::Correlation
+        | Op::Slope
+        | Op::Intercept
+        | Op::WeightedMean => 3,
+        Op::Sum
+        | Op::RangeCheck
+        | Op::Histogram
+        | Op::SumProduct
+        | Op::Query
+        | Op::TopK
+        | Op::BottomK => 0,
     }
 }
 
@@ -212,7 +732,7 @@ fn parse_i128(s: &str) -> i128 {
     let mut acc: i128 = 0;
     while i < b.len() {
         let d = b[i];
-        if d < b'0' || d > b'9' {
+        if !d.is_ascii_digit() {
             panic!("non-digit in integer");
         }
         acc = acc
@@ -0,0 +1,72 @@
+//! Differentially private results: the program adds two-sided geometric noise to a sum or mean,
+//! so exact statistics over small cohorts are never published.
+//!
+//! The noise is drawn from a seed the provider keeps secret, and only the seed's hash is
+//! committed. Publishing that hash before the data is proven stops the provider from trying
+//! seeds until one gives a favourable result, while keeping the noise unknown to everyone else.
+
+use crate::hash;
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Noise {
+    /// The privacy budget in thousandths, so ε = `epsilon_millis / 1000`.
+    pub epsilon_millis: u32,
+    /// Every value in the column must lie in `lower..=upper`, which bounds how much one row can
+    /// move the sum.
+    pub lower: i128,
+    pub upper: i128,
+    /// The secret the noise is drawn from.
+    pub seed: [u8; 32],
+}
+
+impl Noise {
+    /// How much replacing one row can change the sum.
+    pub fn sensitivity(&self) -> i128 {
+        self.upper
+            .checked_sub(self.lower)
+            .expect("sensitivity overflow")
+    }
+
+    /// The hash of the seed, committed as `seedHash`.
+    pub fn seed_hash(&self) -> [u8; 32] {
+        hash(&self.seed)
+    }
+
+    /// Panics unless `value` lies within the bounds.
+    pub fn check_bounds(&self, value: i128) {
+        assert!(
+            (self.lower..=self.upper).contains(&value),
+            "value {value} is outside the noise bounds"
+        );
+    }
+
+    /// Draws noise with `P(k) ∝ exp(-ε|k| / sensitivity)` for a result over `file_root`, as the
+    /// difference of two geometric samples. The draw is tied to the file and the query, so
+    /// reusing a seed elsewhere does not reuse the noise.
+    pub fn sample(&self, file_root: &[u8; 32], col_hash: &[u8; 32], op: u8) -> i128 {
+        assert!(self.epsilon_millis > 0, "epsilon must be positive");
+        let sensitivity = self.sensitivity();
+        assert!(sensitivity > 0, "noise bounds are empty");
+
+        // A geometric sample with P(G >= k) = α^k, where ln α = -ε / sensitivity.
+        let scale = sensitivity as f64 * 1000.0 / self.epsilon_millis as f64;
+        let geometric = |draw: u8| {
+            let mut keccak = Keccak::v256();
+            keccak.update(&self.seed);
+            keccak.update(file_root);
+            keccak.update(col_hash);
+            keccak.update(&[op, draw]);
+            let mut bytes = [0u8; 32];
+            keccak.finalize(&mut bytes);
+
+            // Uniform in (0, 1], so the logarithm is finite.
+            let bits = u64::from_be_bytes(bytes[..8].try_into().unwrap()) >> 11;
+            let uniform = (bits + 1) as f64 / (1u64 << 53) as f64;
+            (-libm::log(uniform) * scale).floor() as i128
+        };
+
+        geometric(0) - geometric(1)
+    }
+}
@@ -0,0 +1,139 @@
+//! Statistics over a pair of columns: covariance, Pearson correlation, the ordinary least
+//! squares fit of the `y` column against the selected `x` column, and the sum of products and
+//! mean of `x` weighted by `y`.
+//!
+//! Everything is computed from the exact sums `Σx`, `Σy`, `Σxy`, `Σx²` and `Σy²`, widened to 256
+//! and 512 bits where the products need it, so the committed result is the exactly rounded value
+//! rather than whatever a float happened to give.
+
+use crate::{decimal_points, parse_i128, Csv, Op};
+use alloy_primitives::{I256, U256, U512};
+
+/// The sums a pairwise statistic is computed from.
+#[derive(Debug, Default)]
+struct Sums {
+    n: i128,
+    x: i128,
+    y: i128,
+    xy: i128,
+    xx: i128,
+    yy: i128,
+}
+
+/// Computes a pairwise statistic of the selected column (`x`) and the column at `y_idx`. Returns
+/// the row count, the result scaled by the op's decimal points and the decimal points.
+pub fn pairwise_col(csv: &Csv, y_idx: usize, op: &Op) -> (u64, i128, u16) {
+    let mut sums = Sums::default();
+    let squares = matches!(op, Op::Slope | Op::Intercept | Op::Correlation);
+    for (x, y) in csv.column().zip(csv.column_at(y_idx)) {
+        let (x, y) = (parse_i128(x), parse_i128(y));
+        let add = |sum: i128, v: i128| sum.checked_add(v).expect("sum overflow");
+        let mul = |a: i128, b: i128| a.checked_mul(b).expect("product overflow");
+        if let Op::WeightedMean = op {
+            assert!(y >= 0, "weight {y} is negative");
+        }
+        sums.n += 1;
+        sums.x = add(sums.x, x);
+        sums.y = add(sums.y, y);
+        sums.xy = add(sums.xy, mul(x, y));
+        // Only the second moments can overflow where the sum of products does not, so they are
+        // skipped by the ops that do not need them.
+        if squares {
+            sums.xx = add(sums.xx, mul(x, x));
+            sums.yy = add(sums.yy, mul(y, y));
+        }
+    }
+    assert!(sums.n > 0, "no rows");
+
+    let decimal = decimal_points(op);
+    let scale = I256::try_from(10_i128.pow(decimal as u32)).unwrap();
+    let n = I256::try_from(sums.n).unwrap();
+    // n times the co-moments, which keeps every quantity an integer.
+    let sxy = comoment(n, sums.xy, sums.x, sums.y);
+    let sxx = comoment(n, sums.xx, sums.x, sums.x);
+
+    let result = match op {
+        // Population covariance: sxy / n².
+        Op::Covariance => div_round(mul(sxy, scale), mul(n, n)),
+        Op::Slope => {
+            assert!(!sxx.is_zero(), "the x column is constant");
+            div_round(mul(sxy, scale), sxx)
+        }
+        // (Σy − slope·Σx) / n, over the common denominator n·sxx.
+        Op::Intercept => {
+            assert!(!sxx.is_zero(), "the x column is constant");
+            let x = I256::try_from(sums.x).unwrap();
+            let y = I256::try_from(sums.y).unwrap();
+            let numerator = mul(y, sxx)
+                .checked_sub(mul(sxy, x))
+                .expect("product overflow");
+            div_round(mul(numerator, scale), mul(n, sxx))
+        }
+        Op::Correlation => {
+            let syy = comoment(n, sums.yy, sums.y, sums.y);
+            assert!(
+                !sxx.is_zero() && !syy.is_zero(),
+                "correlation of a constant column"
+            );
+            correlation(sxy, sxx, syy, scale)
+        }
+        Op::SumProduct => sums.xy,
+        Op::WeightedMean => {
+            assert!(sums.y > 0, "the weights add up to zero");
+            let xy = I256::try_from(sums.xy).unwrap();
+            div_round(mul(xy, scale), I256::try_from(sums.y).unwrap())
+        }
+        _ => panic!("not a pairwise statistic"),
+    };
+
+    (sums.n as u64, result, decimal)
+}
+
+/// `n·Σab − Σa·Σb`, which is `n²` times the covariance of `a` and `b`.
+fn comoment(n: I256, ab: i128, a: i128, b: i128) -> I256 {
+    let [ab, a, b] = [ab, a, b].map(|v| I256::try_from(v).unwrap());
+    mul(n, ab).checked_sub(mul(a, b)).expect("product overflow")
+}
+
+fn mul(a: I256, b: I256) -> I256 {
+    a.checked_mul(b).expect("product overflow")
+}
+
+/// `numerator / denominator` rounded half away from zero. The denominator must be positive.
+fn div_round(numerator: I256, denominator: I256) -> i128 {
+    let magnitude = numerator.unsigned_abs();
+    let denominator = denominator.unsigned_abs();
+    let (mut quotient, remainder) = (magnitude / denominator, magnitude % denominator);
+    if remainder >= denominator - remainder {
+        quotient += U256::from(1);
+    }
+    signed(numerator.is_negative(), quotient)
+}
+
+/// `scale · sxy / √(sxx · syy)` rounded to the nearest integer, without leaving the integers:
+/// the magnitude is the rounded square root of `scale² · sxy² / (sxx · syy)`.
+fn correlation(sxy: I256, sxx: I256, syy: I256, scale: I256) -> i128 {
+    let wide = |v: I256| U512::from(v.unsigned_abs());
+    let mul = |a: U512, b: U512| a.checked_mul(b).expect("product overflow");
+    let numerator = mul(mul(wide(scale), wide(sxy)), mul(wide(scale), wide(sxy)));
+    let denominator = mul(wide(sxx), wide(syy));
+
+    let mut root = (numerator / denominator).root(2);
+    // Round up when √(numerator / denominator) ≥ root + ½, that is when
+    // (2·root + 1)² · denominator ≤ 4 · numerator.
+    let next = root * U512::from(2) + U512::from(1);
+    if mul(mul(next, next), denominator) <= mul(numerator, U512::from(4)) {
+        root += U512::from(1);
+    }
+
+    signed(sxy.is_negative(), U256::from(root))
+}
+
+fn signed(negative: bool, magnitude: U256) -> i128 {
+    let magnitude = i128::try_from(magnitude).expect("result overflow");
+    if negative {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
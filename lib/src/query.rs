@@ -0,0 +1,249 @@
+//! Queries in a small SQL dialect: `SELECT` group keys and aggregates of expressions, `WHERE` a
+//! condition holds, `GROUP BY` columns.
+//!
+//! The vcsv program commits [`QueryValues`] after the usual [`PublicValues`](crate::PublicValues):
+//! the hash of the query's canonical form, which is what [`Query`]'s `Display` prints, and the
+//! result table with one row per group, ordered by key.
+
+use crate::expr::{cell, Resolved};
+use crate::{check_cohort, decimal_points, hash, mean, Csv, Expr, Join, Op};
+use alloy_sol_types::sol;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    struct QueryRow {
+        string[] keys;
+        int128[] values;
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct QueryValues {
+        bytes32 planHash;
+        /// The `GROUP BY` columns followed by the selected aggregates.
+        string[] columns;
+        /// The decimal points of each aggregate's values.
+        uint16[] decimal_points;
+        QueryRow[] rows;
+        /// The Merkle root of each joined file, in the order of the query's joins.
+        bytes32[] joinRoots;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Selected {
+    /// A `GROUP BY` column.
+    Key(String),
+    /// An aggregate of an expression over each group's rows. `COUNT(*)` has no expression.
+    Agg(Aggregate, Option<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Query {
+    pub select: Vec<Selected>,
+    /// The name the query gives the proven file: `data` unless it joins other files, whose
+    /// columns can then be qualified by table.
+    pub from: String,
+    #[serde(default)]
+    pub joins: Vec<Join>,
+    /// Only rows where this is non-zero are aggregated.
+    pub filter: Option<Expr>,
+    pub group_by: Vec<String>,
+}
+
+impl Query {
+    /// The hash committed as `planHash`.
+    pub fn hash(&self) -> [u8; 32] {
+        hash(self.to_string().as_bytes())
+    }
+
+    /// Checks that the query selects at least one aggregate, only selects keys it groups by,
+    /// and only counts rows rather than expressions.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.aggregates().next().is_none() {
+            return Err("select at least one aggregate".to_string());
+        }
+        for selected in &self.select {
+            match selected {
+                Selected::Key(col) if !self.group_by.contains(col) => {
+                    return Err(format!("{col} is selected but not in GROUP BY"));
+                }
+                Selected::Agg(Aggregate::Count, Some(_)) => {
+                    return Err("only COUNT(*) is supported".to_string());
+                }
+                Selected::Agg(Aggregate::Count, None) => {}
+                Selected::Agg(_, None) => return Err("aggregate of nothing".to_string()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Panics unless the query is [valid](Query::validate).
+    pub fn check(&self) {
+        if let Err(message) = self.validate() {
+            panic!("{message}");
+        }
+    }
+
+    fn aggregates(&self) -> impl Iterator<Item = (Aggregate, Option<&Expr>)> {
+        self.select.iter().filter_map(|selected| match selected {
+            Selected::Key(_) => None,
+            Selected::Agg(aggregate, expr) => Some((*aggregate, expr.as_ref())),
+        })
+    }
+}
+
+/// Runs a query over every row. Returns the number of rows matching the filter and the result
+/// table. Panics if any group holds fewer than `min_rows` rows.
+pub fn query(csv: &Csv, query: &Query, min_rows: u64) -> (u64, QueryValues) {
+    query.check();
+    let keys: Vec<usize> = query.group_by.iter().map(|col| csv.index_of(col)).collect();
+    let filter = query.filter.as_ref().map(|filter| filter.resolve(csv));
+    let aggregates: Vec<(Aggregate, Option<Resolved>)> = query
+        .aggregates()
+        .map(|(aggregate, expr)| (aggregate, expr.map(|expr| expr.resolve(csv))))
+        .collect();
+
+    // Each group's row count and the running count, sum, minimum or maximum of each aggregate.
+    let mut groups: BTreeMap<Vec<&str>, (u64, Vec<Option<i128>>)> = BTreeMap::new();
+    let mut n_rows = 0;
+    for row in 0..csv.len() {
+        let cells: Vec<&str> = csv.cells(row).collect();
+        if let Some(filter) = &filter {
+            if filter.eval(&cells) == 0 {
+                continue;
+            }
+        }
+        n_rows += 1;
+
+        let key = keys.iter().map(|&idx| cell(&cells, idx)).collect();
+        let (rows, accs) = groups
+            .entry(key)
+            .or_insert_with(|| (0, vec![None; aggregates.len()]));
+        *rows += 1;
+        for ((aggregate, expr), acc) in aggregates.iter().zip(accs.iter_mut()) {
+            let value = expr.as_ref().map_or(1, |expr| expr.eval(&cells));
+            *acc = Some(match (aggregate, *acc) {
+                (_, None) => value,
+                (Aggregate::Count | Aggregate::Sum | Aggregate::Avg, Some(acc)) => {
+                    acc.checked_add(value).expect("sum overflow")
+                }
+                (Aggregate::Min, Some(acc)) => acc.min(value),
+                (Aggregate::Max, Some(acc)) => acc.max(value),
+            });
+        }
+    }
+    assert!(n_rows > 0, "no rows match the query");
+
+    let mut rows = Vec::with_capacity(groups.len());
+    for (key, (count, accs)) in groups {
+        check_cohort(count, min_rows);
+        let values = aggregates
+            .iter()
+            .zip(accs)
+            .map(|((aggregate, _), acc)| {
+                let acc = acc.unwrap();
+                match aggregate {
+                    Aggregate::Avg => mean(count, acc).1,
+                    _ => acc,
+                }
+            })
+            .collect();
+        rows.push(QueryRow {
+            keys: key.into_iter().map(str::to_string).collect(),
+            values,
+        });
+    }
+
+    let values = QueryValues {
+        planHash: query.hash().into(),
+        columns: query
+            .group_by
+            .iter()
+            .map(|col| Expr::Col(col.clone()).to_string())
+            .chain(query.select.iter().filter_map(|selected| match selected {
+                Selected::Key(_) => None,
+                Selected::Agg(..) => Some(selected.to_string()),
+            }))
+            .collect(),
+        decimal_points: aggregates
+            .iter()
+            .map(|(aggregate, expr)| {
+                let scale = expr.as_ref().map_or(0, |expr| expr.scale) as u16;
+                match aggregate {
+                    Aggregate::Count => 0,
+                    Aggregate::Avg => scale + decimal_points(&Op::Mean),
+                    _ => scale,
+                }
+            })
+            .collect(),
+        rows,
+        joinRoots: Vec::new(),
+    };
+    (n_rows, values)
+}
+
+impl fmt::Display for Selected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selected::Key(col) => write!(f, "{}", Expr::Col(col.clone())),
+            Selected::Agg(aggregate, expr) => {
+                let name = match aggregate {
+                    Aggregate::Count => "COUNT",
+                    Aggregate::Sum => "SUM",
+                    Aggregate::Avg => "AVG",
+                    Aggregate::Min => "MIN",
+                    Aggregate::Max => "MAX",
+                };
+                match expr {
+                    Some(expr) => write!(f, "{name}({expr})"),
+                    None => write!(f, "{name}(*)"),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SELECT ")?;
+        for (i, selected) in self.select.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{selected}")?;
+        }
+        write!(f, " FROM {}", Expr::Col(self.from.clone()))?;
+        for join in &self.joins {
+            write!(
+                f,
+                " JOIN {} ON {} = {}",
+                Expr::Col(join.table.clone()),
+                Expr::Col(join.left.clone()),
+                Expr::Col(join.right.clone())
+            )?;
+        }
+        if let Some(filter) = &self.filter {
+            write!(f, " WHERE {filter}")?;
+        }
+        for (i, col) in self.group_by.iter().enumerate() {
+            let by = if i == 0 { " GROUP BY " } else { ", " };
+            write!(f, "{by}{}", Expr::Col(col.clone()))?;
+        }
+        Ok(())
+    }
+}
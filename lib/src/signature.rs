@@ -0,0 +1,50 @@
+//! EIP-191 signatures over a file root, so a proven `fileRoot` can be tied to the Ethereum
+//! address of the data provider that published it.
+
+use crate::hash;
+use alloy_primitives::Address;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+
+/// The hash a provider signs for a root: `personal_sign` over the 32 raw root bytes.
+pub fn eip191_hash(root: &[u8; 32]) -> [u8; 32] {
+    let mut message = Vec::with_capacity(60);
+    message.extend_from_slice(b"\x19Ethereum Signed Message:\n32");
+    message.extend_from_slice(root);
+    hash(&message)
+}
+
+/// Recovers the address that signed `root` from a 65-byte `r || s || v` signature, where `v`
+/// is 0, 1, 27 or 28. Returns `None` for malformed signatures.
+pub fn recover_signer(root: &[u8; 32], signature: &[u8; 65]) -> Option<Address> {
+    let v = match signature[64] {
+        v @ (0 | 1) => v,
+        v @ (27 | 28) => v - 27,
+        _ => return None,
+    };
+    let recovery_id = RecoveryId::from_byte(v)?;
+    let signature = Signature::from_slice(&signature[..64]).ok()?;
+
+    let key =
+        VerifyingKey::recover_from_prehash(&eip191_hash(root), &signature, recovery_id).ok()?;
+    Some(address(&key))
+}
+
+/// The signer the programs commit for `root`: the address recovered from `signature`, or the
+/// zero address when the root is unsigned. Panics if the signature is malformed.
+///
+/// Inside the zkVM, recovery runs on SP1's secp256k1 precompile through the patched `k256` the
+/// guest programs are built with.
+pub fn signer(root: &[u8; 32], signature: Option<&[u8]>) -> Address {
+    let Some(signature) = signature else {
+        return Address::ZERO;
+    };
+    let signature: &[u8; 65] = signature.try_into().expect("signature is not 65 bytes");
+    recover_signer(root, signature).expect("invalid root signature")
+}
+
+/// The Ethereum address of a public key.
+pub fn address(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    let digest = hash(&point.as_bytes()[1..]);
+    Address::from_slice(&digest[12..])
+}
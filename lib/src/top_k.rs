@@ -0,0 +1,59 @@
+//! The `k` largest or smallest values of a column, as their sum or as the rows themselves.
+//!
+//! Ties are broken by row, so the earlier row ranks first. The program commits `k` and the sum of
+//! the selected values in the [`PublicValues`](crate::PublicValues). When the rows are revealed, it
+//! also commits [`TopKValues`] after them, in rank order: each row's index, its Merkle leaf and its
+//! value. An inclusion proof of that leaf at that index links the row back to `fileRoot`.
+
+use crate::{hash_row, parse_i128, Csv, Op};
+use alloy_sol_types::sol;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    struct TopKValues {
+        uint64[] rows;
+        bytes32[] leaves;
+        int128[] values;
+    }
+}
+
+/// Selects the `k` largest values for [`Op::TopK`], or the smallest for [`Op::BottomK`]. Returns
+/// the row count, the sum of the selected values and, if `reveal`, the selected rows.
+pub fn top_k(csv: &Csv, k: u64, op: &Op, reveal: bool) -> (u64, i128, Option<TopKValues>) {
+    let n_rows = csv.len() as u64;
+    assert!(k > 0, "k must be at least 1");
+    assert!(k <= n_rows, "k is larger than the {n_rows} rows");
+
+    let mut ranked: Vec<(i128, usize)> = csv
+        .column()
+        .map(parse_i128)
+        .enumerate()
+        .map(|(row, value)| (value, row))
+        .collect();
+    let rank = |a: &(i128, usize), b: &(i128, usize)| match op {
+        Op::TopK => b.0.cmp(&a.0).then(a.1.cmp(&b.1)),
+        Op::BottomK => a.0.cmp(&b.0).then(a.1.cmp(&b.1)),
+        _ => panic!("only top-k and bottom-k select rows"),
+    };
+    // Partition around the k-th value first, so only the selected rows are sorted.
+    let k = k as usize;
+    if k < ranked.len() {
+        ranked.select_nth_unstable_by(k - 1, rank);
+        ranked.truncate(k);
+    }
+    ranked.sort_unstable_by(rank);
+
+    let sum = ranked
+        .iter()
+        .try_fold(0i128, |sum, (value, _)| sum.checked_add(*value))
+        .expect("sum overflow");
+    let values = reveal.then(|| TopKValues {
+        rows: ranked.iter().map(|(_, row)| *row as u64).collect(),
+        leaves: ranked
+            .iter()
+            .map(|(_, row)| hash_row(csv.row(*row)).into())
+            .collect(),
+        values: ranked.iter().map(|(value, _)| *value).collect(),
+    });
+    (n_rows, sum, values)
+}
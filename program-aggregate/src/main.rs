@@ -0,0 +1,31 @@
+//! Verifies the chunk proofs of a file and commits the same values as the vcsv program, followed
+//! by the digest of the chunk program's verifying key.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use alloy_sol_types::SolType;
+use sha2::{Digest, Sha256};
+use vcsv_lib::{aggregate, vkey_bytes, AggregateInput, ChunkValues, PublicValues};
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<AggregateInput>();
+
+    let chunks: Vec<ChunkValues> = input
+        .chunks
+        .iter()
+        .map(|public_values| {
+            let digest: [u8; 32] = Sha256::digest(public_values).into();
+            sp1_zkvm::lib::verify::verify_sp1_proof(&input.chunk_vkey, &digest);
+
+            ChunkValues::abi_decode(public_values).expect("invalid chunk public values")
+        })
+        .collect();
+
+    let public = aggregate(&input, &chunks);
+
+    let mut bytes = PublicValues::abi_encode(&public);
+    bytes.extend_from_slice(&vkey_bytes(&input.chunk_vkey));
+
+    sp1_zkvm::io::commit_slice(&bytes);
+}
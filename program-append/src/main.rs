@@ -0,0 +1,23 @@
+//! Verifies a previous proof and extends it with appended rows, committing the same values as
+//! the vcsv program followed by the running sum.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use alloy_sol_types::SolType;
+use sha2::{Digest, Sha256};
+use vcsv_lib::{append, AppendInput, AppendValues, PublicValues};
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<AppendInput>();
+
+    let digest: [u8; 32] = Sha256::digest(&input.prev_public_values).into();
+    sp1_zkvm::lib::verify::verify_sp1_proof(&input.prev_vkey, &digest);
+
+    let (public, values, _) = append(input);
+
+    let mut bytes = PublicValues::abi_encode(&public);
+    bytes.extend_from_slice(&AppendValues::abi_encode(&values));
+
+    sp1_zkvm::io::commit_slice(&bytes);
+}
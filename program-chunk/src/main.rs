@@ -0,0 +1,15 @@
+//! Proves the partial result and Merkle subtree root of one chunk of rows.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use alloy_sol_types::SolType;
+use vcsv_lib::{chunk_values, ChunkInput, ChunkValues};
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<ChunkInput>();
+
+    let chunk = chunk_values(input);
+
+    sp1_zkvm::io::commit_slice(&ChunkValues::abi_encode(&chunk));
+}
@@ -0,0 +1,15 @@
+//! Proves which rows differ between two versions of a file.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use alloy_sol_types::SolType;
+use vcsv_lib::{diff, DiffInput, DiffValues};
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<DiffInput>();
+
+    let (values, _) = diff(input);
+
+    sp1_zkvm::io::commit_slice(&DiffValues::abi_encode(&values));
+}
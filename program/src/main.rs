@@ -2,39 +2,21 @@
 sp1_zkvm::entrypoint!(main);
 
 use alloy_sol_types::SolType;
-use vcsv_lib::{
-    hash, mean_col, median_col, merkelize, op_to_u8, parse_csv, sum_col, Input, Op, PublicValues,
-};
+use vcsv_lib::{run_with_table, Input, PublicValues};
 
 pub fn main() {
     // Read an input to the program.
     //
     // Behind the scenes, this compiles down to a custom system call which handles reading inputs
     // from the prover.
-    let Input { csv, col, op } = sp1_zkvm::io::read::<Input>();
+    let input = sp1_zkvm::io::read::<Input>();
 
-    let csv_cont = parse_csv(csv, Some(&col));
-    let file_root = merkelize(&csv_cont);
+    let (public, table) = run_with_table(input);
 
-    let (n_rows, result, decimal_points) = match op {
-        Op::Sum => sum_col(&csv_cont),
-        Op::Mean => mean_col(&csv_cont),
-        Op::Median => median_col(&csv_cont),
-    };
-
-    let col_bytes = col.as_bytes();
-    let col_hash = hash(col_bytes);
-
-    let public = PublicValues {
-        fileRoot: file_root.into(),
-        op: op_to_u8(op),
-        colHash: col_hash.into(),
-        n_rows,
-        result,
-        decimal_points,
-    };
-
-    let bytes = PublicValues::abi_encode(&public);
+    let mut bytes = PublicValues::abi_encode(&public);
+    if let Some(table) = table {
+        bytes.extend_from_slice(&table.abi_encode());
+    }
 
     sp1_zkvm::io::commit_slice(&bytes);
 }
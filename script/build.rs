@@ -1,5 +1,35 @@
 use sp1_build::build_program_with_args;
+use std::{env, fs, path::PathBuf};
+
+/// The guest programs, by directory and package name.
+const PROGRAMS: [(&str, &str); 5] = [
+    ("../program", "vcsv-program"),
+    ("../program-chunk", "vcsv-chunk-program"),
+    ("../program-aggregate", "vcsv-aggregate-program"),
+    ("../program-append", "vcsv-append-program"),
+    ("../program-diff", "vcsv-diff-program"),
+];
 
 fn main() {
-    build_program_with_args("../program", Default::default())
+    // Mirrors how sp1-build decides to skip the guest programs, whose ELFs are then empty. Tests
+    // that run a guest are ignored unless `guest_programs` is set.
+    println!("cargo::rustc-check-cfg=cfg(guest_programs)");
+    println!("cargo::rerun-if-env-changed=SP1_SKIP_PROGRAM_BUILD");
+    let skipped =
+        env::var("SP1_SKIP_PROGRAM_BUILD").is_ok_and(|skip| skip.eq_ignore_ascii_case("true"));
+    if !skipped {
+        println!("cargo::rustc-cfg=guest_programs");
+    }
+
+    for (dir, name) in PROGRAMS {
+        if skipped {
+            // sp1-build resolves a skipped program's dependencies too, which fetches the patched
+            // crates from the network. Point `include_elf!` at an empty ELF instead.
+            let elf = PathBuf::from(env::var("OUT_DIR").unwrap()).join(name);
+            fs::write(&elf, []).expect("failed to write the empty ELF");
+            println!("cargo::rustc-env=SP1_ELF_{name}={}", elf.display());
+        } else {
+            build_program_with_args(dir, Default::default());
+        }
+    }
 }
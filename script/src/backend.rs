@@ -0,0 +1,83 @@
+//! Prover backend configuration.
+//!
+//! The prover client is built from an explicit [`BackendConfig`] instead of the `SP1_PROVER` and
+//! `NETWORK_PRIVATE_KEY` environment variables, so the network key is never written into the
+//! process environment where child processes could inherit it.
+
+use sp1_prover::components::CpuProverComponents;
+use sp1_sdk::{Prover, ProverClient};
+use std::{env, fs, io, path::PathBuf};
+
+/// The prover client used to prove and verify the vcsv program.
+pub type VcsvProver = Box<dyn Prover<CpuProverComponents>>;
+
+/// Which prover to build, with everything it needs.
+#[derive(Clone)]
+pub enum BackendConfig {
+    /// Prove locally on the CPU.
+    Cpu,
+    /// Execute the program and return a mock proof carrying the real public values.
+    Mock,
+    /// Request proofs from the Succinct Prover Network.
+    Network { private_key: String },
+}
+
+impl BackendConfig {
+    /// Builds the prover client for this backend.
+    pub fn client(&self) -> VcsvProver {
+        match self {
+            BackendConfig::Cpu => Box::new(ProverClient::builder().cpu().build()),
+            BackendConfig::Mock => Box::new(ProverClient::builder().mock().build()),
+            BackendConfig::Network { private_key } => Box::new(
+                ProverClient::builder()
+                    .network()
+                    .private_key(private_key)
+                    .build(),
+            ),
+        }
+    }
+}
+
+// Keep the key out of `{:?}` output and logs.
+impl std::fmt::Debug for BackendConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackendConfig::Cpu => write!(f, "Cpu"),
+            BackendConfig::Mock => write!(f, "Mock"),
+            BackendConfig::Network { .. } => write!(f, "Network {{ private_key: <redacted> }}"),
+        }
+    }
+}
+
+/// Where a private key, such as the Succinct Prover Network key, is read from.
+#[derive(Debug, Clone)]
+pub enum KeySource {
+    /// A file containing the key, e.g. one only readable by the current user.
+    File(PathBuf),
+    /// An environment variable that is already set, e.g. `NETWORK_PRIVATE_KEY`.
+    Env(String),
+    /// Ask for the key on the terminal, without echoing it.
+    Prompt,
+}
+
+impl KeySource {
+    /// Reads the key, trimming surrounding whitespace.
+    pub fn read(&self) -> io::Result<String> {
+        let key = match self {
+            KeySource::File(path) => fs::read_to_string(path)?,
+            KeySource::Env(name) => env::var(name).map_err(|_| {
+                io::Error::new(io::ErrorKind::NotFound, format!("{name} is not set"))
+            })?,
+            KeySource::Prompt => rpassword::prompt_password("Private key: ")?,
+        };
+
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "private key is empty",
+            ));
+        }
+        Ok(key.to_string())
+    }
+}
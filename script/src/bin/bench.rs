@@ -0,0 +1,54 @@
+//! Reports how many zkVM cycles each op takes on the example data and on a generated file.
+//!
+//! Run it before and after a change to the guest program to compare cycle counts:
+//! ```shell
+//! cargo run --release --bin bench -- --rows 10000
+//! ```
+
+use clap::Parser;
+use std::{env, fs, path::PathBuf};
+use vcsv_lib::Op;
+use vcsv_script::{read_input, BackendConfig, VcsvClient};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct BenchArgs {
+    /// Number of rows in the generated file.
+    #[arg(long, default_value = "10000")]
+    rows: usize,
+}
+
+fn main() {
+    let args = BenchArgs::parse();
+    let repo = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..");
+
+    let generated = env::temp_dir().join(format!("vcsv_bench_{}.csv", args.rows));
+    fs::write(&generated, generate(args.rows)).expect("failed to write generated csv");
+
+    let client = VcsvClient::new(BackendConfig::Cpu);
+    let files = [repo.join("examples/medium.csv"), generated];
+
+    println!("{:<32} {:>8} {:>14}", "file", "op", "cycles");
+    for file in &files {
+        for op in [Op::Sum, Op::Mean, Op::Median] {
+            let input = read_input(file, op.clone(), "price").expect("failed to read csv");
+            let execution = client.execute(&input).expect("failed to execute program");
+
+            println!(
+                "{:<32} {:>8} {:>14}",
+                file.file_name().unwrap().to_string_lossy(),
+                format!("{op:?}").to_lowercase(),
+                execution.report.total_instruction_count()
+            );
+        }
+    }
+}
+
+/// A file shaped like `examples/medium.csv`, with some padding around cells.
+fn generate(rows: usize) -> String {
+    let mut csv = String::from("id,price,qty\n");
+    for i in 0..rows {
+        csv.push_str(&format!("{}, {} ,{}\n", i + 1, (i * 37) % 500, i % 9 + 1));
+    }
+    csv
+}
@@ -90,12 +90,7 @@ fn create_proof_fixture(
     // Deserialize the public values.
     let bytes = proof.public_values.as_slice();
     let PublicValues {
-        fileRoot,
-        op,
-        colHash,
-        n_rows,
-        result,
-        decimal_points,
+        op, n_rows, result, ..
     } = PublicValues::abi_decode(bytes).unwrap();
 
     // Create the testing fixture so we can test things end-to-end.
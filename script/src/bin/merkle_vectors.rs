@@ -0,0 +1,78 @@
+//! Generates Merkle inclusion vectors for the Solidity verifier in `contracts/`.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! cargo run --release --bin merkle-vectors
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+use vcsv_lib::{merkelize, parse_csv};
+use vcsv_script::inclusion_proof;
+
+/// The example files the vectors are generated from, relative to the repository root.
+const FILES: &[&str] = &["examples/tiny.csv", "examples/medium.csv"];
+
+/// A single row inclusion proof, hex encoded the way the Solidity tests parse it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct InclusionVector {
+    file: String,
+    index: u64,
+    row: String,
+    leaf: String,
+    siblings: Vec<String>,
+    root: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct InclusionVectors {
+    count: u64,
+    vectors: Vec<InclusionVector>,
+}
+
+fn main() {
+    let repo = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..");
+
+    let mut vectors = Vec::new();
+    for file in FILES {
+        let path = repo.join(file);
+        let csv = parse_csv(fs::read(&path).expect("failed to read csv"), None);
+        let root = merkelize(&csv);
+
+        for index in 0..csv.len() {
+            let proof =
+                inclusion_proof(path.clone(), index).expect("failed to build inclusion proof");
+
+            vectors.push(InclusionVector {
+                file: file.to_string(),
+                index: index as u64,
+                row: format!(
+                    "0x{}",
+                    hex::encode(csv.cells(index).collect::<Vec<_>>().join(","))
+                ),
+                leaf: format!("0x{}", hex::encode(proof.leaf)),
+                siblings: proof
+                    .siblings
+                    .iter()
+                    .map(|h| format!("0x{}", hex::encode(h)))
+                    .collect(),
+                root: format!("0x{}", hex::encode(root)),
+            });
+        }
+    }
+
+    let fixture = InclusionVectors {
+        count: vectors.len() as u64,
+        vectors,
+    };
+
+    let fixture_path = repo.join("contracts/src/fixtures");
+    fs::create_dir_all(&fixture_path).expect("failed to create fixture path");
+    fs::write(
+        fixture_path.join("merkle-vectors.json"),
+        serde_json::to_string_pretty(&fixture).unwrap(),
+    )
+    .expect("failed to write fixture");
+
+    println!("Wrote {} vectors", fixture.count);
+}
@@ -0,0 +1,558 @@
+use crate::stream::read_frontier;
+use crate::{
+    BackendConfig, VcsvError, VcsvProver, VCSV_AGGREGATE_ELF, VCSV_APPEND_ELF, VCSV_CHUNK_ELF,
+    VCSV_DIFF_ELF, VCSV_ELF,
+};
+use alloy_sol_types::SolType;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{
+    ExecutionReport, HashableKey, ProverClient, SP1Proof, SP1ProofMode, SP1ProofWithPublicValues,
+    SP1ProvingKey, SP1Stdin, SP1VerifyingKey,
+};
+use std::{fs, path::Path, sync::OnceLock};
+use vcsv_lib::{
+    aggregate, append, changes_digest, diff, median_pivots, op_to_u8, run, split_chunks,
+    vkey_bytes, AggregateInput, AppendInput, AppendValues, ChunkInput, ChunkValues, DiffInput,
+    DiffValues, HistogramValues, Input, MerkleFrontier, Op, ProgramKeys, ProofType, PublicValues,
+    QueryValues, RowChange, Table, TopKValues,
+};
+
+/// The guest programs a [`VcsvClient`] can prove.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Program {
+    /// Computes an op over a whole file in one proof.
+    #[default]
+    Vcsv,
+    /// Computes the partial result of one chunk of rows.
+    Chunk,
+    /// Recursively verifies chunk proofs and combines their results.
+    Aggregate,
+    /// Recursively verifies a previous proof and extends it with appended rows.
+    Append,
+    /// Proves which rows differ between two versions of a file.
+    Diff,
+}
+
+impl Program {
+    const COUNT: usize = 5;
+
+    pub fn elf(self) -> &'static [u8] {
+        match self {
+            Program::Vcsv => VCSV_ELF,
+            Program::Chunk => VCSV_CHUNK_ELF,
+            Program::Aggregate => VCSV_AGGREGATE_ELF,
+            Program::Append => VCSV_APPEND_ELF,
+            Program::Diff => VCSV_DIFF_ELF,
+        }
+    }
+}
+
+/// A proof as written to disk by `vcsv prove`, tagged with the kind of proof it holds and the
+/// program that produced it.
+#[derive(Serialize, Deserialize)]
+pub struct ProofBundle {
+    pub proof_type: ProofType,
+    /// Bundles written before chunked proving existed are always [`Program::Vcsv`].
+    #[serde(default)]
+    pub program: Program,
+    pub proof: SP1ProofWithPublicValues,
+    /// The Merkle frontier of the proven file, so rows can be appended without the file.
+    /// Not covered by the proof; the append program checks it against `fileRoot`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frontier: Option<MerkleFrontier>,
+    /// The changed rows a diff proof attests to. Not covered by the proof itself; verifying
+    /// checks them against the committed `changesDigest`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changes: Option<Vec<RowChange>>,
+    /// The result behind `resultCommitment`, for the prover to reveal later. Never written.
+    #[serde(skip)]
+    pub hidden_result: Option<HiddenResult>,
+}
+
+/// A result committed as `keccak(result || nonce)` instead of being revealed.
+#[derive(Clone, Debug)]
+pub struct HiddenResult {
+    pub result: i128,
+    pub nonce: [u8; 32],
+}
+
+impl ProofBundle {
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, VcsvError> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), VcsvError> {
+        Ok(fs::write(path, serde_json::to_vec_pretty(self)?)?)
+    }
+
+    /// Decodes the values committed by the program.
+    pub fn public_values(&self) -> Result<PublicValues, VcsvError> {
+        let (public_values, _) = self.split_public_values();
+        Ok(PublicValues::abi_decode(public_values)?)
+    }
+
+    /// The chunk program's verifying key digest an aggregated proof was built from.
+    pub fn chunk_vkey(&self) -> Option<[u8; 32]> {
+        match self.program {
+            Program::Aggregate => self.split_public_values().1.try_into().ok(),
+            _ => None,
+        }
+    }
+
+    /// The running sum and trusted programs committed by an append proof.
+    pub fn append_values(&self) -> Result<Option<AppendValues>, VcsvError> {
+        match self.program {
+            Program::Append => Ok(Some(AppendValues::abi_decode(
+                self.split_public_values().1,
+            )?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// The histogram, query result or revealed rows committed by a proof of [`Op::Histogram`],
+    /// [`Op::Query`], [`Op::TopK`] or [`Op::BottomK`].
+    pub fn table(&self) -> Result<Option<Table>, VcsvError> {
+        match self.program {
+            Program::Vcsv => split_table(self.proof.public_values.as_slice()),
+            _ => Ok(None),
+        }
+    }
+
+    /// Decodes the values committed by a diff proof.
+    pub fn diff_values(&self) -> Result<DiffValues, VcsvError> {
+        Ok(DiffValues::abi_decode(self.proof.public_values.as_slice())?)
+    }
+
+    /// Splits the committed bytes into the [`PublicValues`] and whatever the program appends
+    /// after them.
+    fn split_public_values(&self) -> (&[u8], &[u8]) {
+        let bytes = self.proof.public_values.as_slice();
+        bytes.split_at(bytes.len().min(PublicValues::ENCODED_SIZE.unwrap()))
+    }
+}
+
+/// The result of running the program without proving it.
+pub struct Execution {
+    pub public_values: PublicValues,
+    pub table: Option<Table>,
+    pub report: ExecutionReport,
+}
+
+/// Executes, proves and verifies the vcsv program.
+///
+/// The proving and verifying keys are only set up the first time they are needed, so a client
+/// that just executes never pays for key generation.
+pub struct VcsvClient {
+    prover: VcsvProver,
+    mock: bool,
+    keys: [OnceLock<(SP1ProvingKey, SP1VerifyingKey)>; Program::COUNT],
+}
+
+impl VcsvClient {
+    pub fn new(backend: BackendConfig) -> Self {
+        Self {
+            prover: backend.client(),
+            mock: matches!(backend, BackendConfig::Mock),
+            keys: Default::default(),
+        }
+    }
+
+    pub fn verifying_key(&self) -> &SP1VerifyingKey {
+        self.program_verifying_key(Program::Vcsv)
+    }
+
+    pub fn program_verifying_key(&self, program: Program) -> &SP1VerifyingKey {
+        &self.keys(program).1
+    }
+
+    pub fn execute(&self, input: &Input) -> Result<Execution, VcsvError> {
+        let (output, report) = self
+            .prover
+            .execute(VCSV_ELF, &stdin(input))
+            .map_err(VcsvError::Execution)?;
+
+        let size = output
+            .as_slice()
+            .len()
+            .min(PublicValues::ENCODED_SIZE.unwrap());
+        Ok(Execution {
+            public_values: PublicValues::abi_decode(&output.as_slice()[..size])?,
+            table: split_table(output.as_slice())?,
+            report,
+        })
+    }
+
+    pub fn prove(&self, input: &Input, proof_type: ProofType) -> Result<ProofBundle, VcsvError> {
+        let proof = self
+            .prover
+            .prove(
+                &self.keys(Program::Vcsv).0,
+                &stdin(input),
+                proof_mode(proof_type),
+            )
+            .map_err(VcsvError::Proving)?;
+        let hidden_result = input.result_nonce.map(|nonce| HiddenResult {
+            // The program accepted this input, so re-running it on the host cannot panic.
+            result: run(Input {
+                result_nonce: None,
+                ..input.clone()
+            })
+            .result,
+            nonce,
+        });
+
+        Ok(ProofBundle {
+            proof_type,
+            program: Program::Vcsv,
+            proof,
+            frontier: Some(read_frontier(input.csv.as_slice())?),
+            changes: None,
+            hidden_result,
+        })
+    }
+
+    /// Proves an input in chunks of `chunk_rows` rows, then aggregates the chunk proofs into a
+    /// single proof of the same [`PublicValues`] [`Self::prove`] commits to.
+    ///
+    /// `chunk_rows` must be a power of two so every full chunk is a subtree of the file's
+    /// Merkle tree.
+    pub fn prove_chunked(
+        &self,
+        input: &Input,
+        chunk_rows: usize,
+        proof_type: ProofType,
+    ) -> Result<ProofBundle, VcsvError> {
+        if !chunk_rows.is_power_of_two() {
+            return Err(VcsvError::InvalidChunkSize(chunk_rows));
+        }
+        if input.noise.is_some() {
+            return Err(VcsvError::Unchunkable("noised results"));
+        }
+        if input.expr.is_some() {
+            return Err(VcsvError::Unchunkable("expressions"));
+        }
+        match input.op {
+            Op::RangeCheck => return Err(VcsvError::Unchunkable("range checks")),
+            Op::Histogram => return Err(VcsvError::Unchunkable("histograms")),
+            Op::Query => return Err(VcsvError::Unchunkable("queries")),
+            Op::TopK | Op::BottomK => return Err(VcsvError::Unchunkable("top-k selections")),
+            ref op if op.is_pairwise() => {
+                return Err(VcsvError::Unchunkable("pairwise statistics"))
+            }
+            _ => {}
+        }
+
+        let chunks = split_chunks(&input.csv, chunk_rows);
+        if chunks.is_empty() {
+            return Err(VcsvError::EmptyCsv);
+        }
+
+        let pivots = matches!(input.op, Op::Median).then(|| median_pivots(&chunks, &input.col));
+
+        let (chunk_pk, chunk_vk) = self.keys(Program::Chunk);
+        let mut chunk_proofs = Vec::with_capacity(chunks.len());
+        for (first_row, csv) in chunks {
+            let chunk = ChunkInput {
+                csv,
+                col: input.col.clone(),
+                op: input.op.clone(),
+                first_row,
+                pivots,
+            };
+
+            // Chunks are recursively verified, so they must be compressed proofs.
+            let mut stdin = SP1Stdin::new();
+            stdin.write(&chunk);
+            let proof = self
+                .prover
+                .prove(chunk_pk, &stdin, SP1ProofMode::Compressed)
+                .map_err(VcsvError::Proving)?;
+            chunk_proofs.push(proof);
+        }
+
+        let aggregate_input = AggregateInput {
+            chunk_vkey: chunk_vk.hash_u32(),
+            chunk_height: chunk_rows.trailing_zeros() as u8,
+            col: input.col.clone(),
+            op: input.op.clone(),
+            chunks: chunk_proofs
+                .iter()
+                .map(|proof| proof.public_values.to_vec())
+                .collect(),
+            signature: input.signature.clone(),
+            min_rows: input.min_rows,
+            claim: input.claim.clone(),
+            result_nonce: input.result_nonce,
+        };
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&aggregate_input);
+        for proof in chunk_proofs {
+            let SP1Proof::Compressed(proof) = proof.proof else {
+                unreachable!("chunks are proven in compressed mode");
+            };
+            stdin.write_proof(*proof, chunk_vk.vk.clone());
+        }
+
+        let proof = self.prove_recursive(Program::Aggregate, &stdin, proof_type)?;
+        // Combining the chunks' committed values again only takes a pass over the chunks.
+        let hidden_result = match input.result_nonce {
+            Some(nonce) => {
+                let chunks = aggregate_input
+                    .chunks
+                    .iter()
+                    .map(|bytes| ChunkValues::abi_decode(bytes))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let aggregate_input = AggregateInput {
+                    result_nonce: None,
+                    ..aggregate_input
+                };
+                Some(HiddenResult {
+                    result: aggregate(&aggregate_input, &chunks).result,
+                    nonce,
+                })
+            }
+            None => None,
+        };
+
+        Ok(ProofBundle {
+            proof_type,
+            program: Program::Aggregate,
+            proof,
+            frontier: Some(read_frontier(input.csv.as_slice())?),
+            changes: None,
+            hidden_result,
+        })
+    }
+
+    /// Proves the rows in `input` appended to the file `prev` was proven over, without
+    /// re-reading the earlier rows. `input.csv` holds a header line and only the new rows.
+    ///
+    /// `prev` must be a compressed proof that carries its Merkle frontier, and either a sum or
+    /// an earlier append, so the exact running sum is known. The new proof supports
+    /// [`Op::Sum`] and [`Op::Mean`]. A signature in `input` must be over the extended file's root.
+    pub fn append(
+        &self,
+        prev: &ProofBundle,
+        input: &Input,
+        proof_type: ProofType,
+    ) -> Result<ProofBundle, VcsvError> {
+        let SP1Proof::Compressed(prev_proof) = &prev.proof.proof else {
+            return Err(VcsvError::NotRecursive(prev.proof_type));
+        };
+        let prev_frontier = prev.frontier.clone().ok_or(VcsvError::MissingFrontier)?;
+        let prev_vk = self.program_verifying_key(prev.program);
+
+        let append_input = AppendInput {
+            keys: self.program_keys(),
+            prev_vkey: prev_vk.hash_u32(),
+            prev_public_values: prev.proof.public_values.to_vec(),
+            frontier: prev_frontier,
+            csv: input.csv.clone(),
+            col: input.col.clone(),
+            op: input.op.clone(),
+            signature: input.signature.clone(),
+            min_rows: input.min_rows,
+        };
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&append_input);
+        stdin.write_proof(*prev_proof.clone(), prev_vk.vk.clone());
+
+        let proof = self.prove_recursive(Program::Append, &stdin, proof_type)?;
+        // The program accepted this input, so re-running it on the host cannot panic.
+        let (_, _, frontier) = append(append_input);
+
+        Ok(ProofBundle {
+            proof_type,
+            program: Program::Append,
+            proof,
+            frontier: Some(frontier),
+            changes: None,
+            hidden_result: None,
+        })
+    }
+
+    /// Proves which rows differ between two versions of a file.
+    pub fn prove_diff(
+        &self,
+        old: Vec<u8>,
+        new: Vec<u8>,
+        proof_type: ProofType,
+    ) -> Result<ProofBundle, VcsvError> {
+        let input = DiffInput { old, new };
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&input);
+
+        let proof = self
+            .prover
+            .prove(&self.keys(Program::Diff).0, &stdin, proof_mode(proof_type))
+            .map_err(VcsvError::Proving)?;
+        // The program accepted this input, so re-running it on the host cannot panic.
+        let (_, changes) = diff(input);
+
+        Ok(ProofBundle {
+            proof_type,
+            program: Program::Diff,
+            proof,
+            frontier: None,
+            changes: Some(changes),
+            hidden_result: None,
+        })
+    }
+
+    /// The verifying key digests an append proof commits to trusting.
+    pub fn program_keys(&self) -> ProgramKeys {
+        let key = |program| self.program_verifying_key(program).hash_u32();
+        ProgramKeys {
+            vcsv: key(Program::Vcsv),
+            chunk: key(Program::Chunk),
+            aggregate: key(Program::Aggregate),
+            append: key(Program::Append),
+        }
+    }
+
+    /// Verifies a proof against the vcsv program and returns the values it attests to.
+    pub fn verify(&self, bundle: &ProofBundle) -> Result<PublicValues, VcsvError> {
+        self.verify_proof(bundle)?;
+
+        match bundle.program {
+            Program::Vcsv => {}
+            Program::Aggregate => {
+                let expected = vkey_bytes(&self.program_verifying_key(Program::Chunk).hash_u32());
+                if bundle.chunk_vkey() != Some(expected) {
+                    return Err(VcsvError::ProgramKeyMismatch);
+                }
+            }
+            Program::Append => {
+                let values = bundle.append_values()?.unwrap();
+                if values.programs != self.program_keys().digest() {
+                    return Err(VcsvError::ProgramKeyMismatch);
+                }
+            }
+            Program::Chunk | Program::Diff => {
+                return Err(VcsvError::UnsupportedProgram(bundle.program))
+            }
+        }
+        bundle.public_values()
+    }
+
+    /// Verifies a diff proof and checks the bundled list of changes against it.
+    pub fn verify_diff(&self, bundle: &ProofBundle) -> Result<DiffValues, VcsvError> {
+        if bundle.program != Program::Diff {
+            return Err(VcsvError::UnsupportedProgram(bundle.program));
+        }
+        self.verify_proof(bundle)?;
+
+        let values = bundle.diff_values()?;
+        let changes = bundle.changes.as_deref().unwrap_or_default();
+        if changes_digest(changes) != values.changesDigest {
+            return Err(VcsvError::ChangesMismatch);
+        }
+        Ok(values)
+    }
+
+    /// Checks the proof itself against the verifying key of the program that produced it.
+    fn verify_proof(&self, bundle: &ProofBundle) -> Result<(), VcsvError> {
+        let actual = proof_type_of(&bundle.proof.proof);
+        if actual != bundle.proof_type {
+            return Err(VcsvError::ProofTypeMismatch {
+                recorded: bundle.proof_type,
+                actual,
+            });
+        }
+
+        Ok(self
+            .prover
+            .verify(&bundle.proof, self.program_verifying_key(bundle.program))?)
+    }
+
+    /// Proves a program that verifies the proofs written to `stdin`.
+    fn prove_recursive(
+        &self,
+        program: Program,
+        stdin: &SP1Stdin,
+        proof_type: ProofType,
+    ) -> Result<SP1ProofWithPublicValues, VcsvError> {
+        let pk = &self.keys(program).0;
+        let mode = proof_mode(proof_type);
+        if self.mock {
+            // Mock proofs cannot be verified inside another program.
+            ProverClient::builder()
+                .mock()
+                .build()
+                .prove(pk, stdin)
+                .mode(mode)
+                .deferred_proof_verification(false)
+                .run()
+        } else {
+            self.prover.prove(pk, stdin, mode)
+        }
+        .map_err(VcsvError::Proving)
+    }
+
+    fn keys(&self, program: Program) -> &(SP1ProvingKey, SP1VerifyingKey) {
+        self.keys[program as usize].get_or_init(|| self.prover.setup(program.elf()))
+    }
+}
+
+/// Reads a CSV file into the program input.
+pub fn read_input(
+    file: impl AsRef<Path>,
+    op: Op,
+    col: impl Into<String>,
+) -> Result<Input, VcsvError> {
+    Ok(Input {
+        csv: fs::read(file)?,
+        col: col.into(),
+        op,
+        ..Default::default()
+    })
+}
+
+/// Decodes the table the vcsv program commits after the [`PublicValues`] of a histogram, a query
+/// or revealed top-k rows.
+fn split_table(bytes: &[u8]) -> Result<Option<Table>, VcsvError> {
+    let size = PublicValues::ENCODED_SIZE.unwrap();
+    if bytes.len() < size {
+        return Ok(None);
+    }
+    let (public_values, rest) = bytes.split_at(size);
+    let op = PublicValues::abi_decode(public_values)?.op;
+    Ok(match op {
+        op if op == op_to_u8(Op::Histogram) => {
+            Some(Table::Histogram(HistogramValues::abi_decode(rest)?))
+        }
+        op if op == op_to_u8(Op::Query) => Some(Table::Query(QueryValues::abi_decode(rest)?)),
+        _ if rest.is_empty() => None,
+        op if op == op_to_u8(Op::TopK) || op == op_to_u8(Op::BottomK) => {
+            Some(Table::TopK(TopKValues::abi_decode(rest)?))
+        }
+        _ => None,
+    })
+}
+
+/// Returns the SP1 proof mode that produces a [`ProofType`].
+pub fn proof_mode(proof_type: ProofType) -> SP1ProofMode {
+    match proof_type {
+        ProofType::Core => SP1ProofMode::Core,
+        ProofType::Compressed => SP1ProofMode::Compressed,
+        ProofType::Groth16 => SP1ProofMode::Groth16,
+        ProofType::Plonk => SP1ProofMode::Plonk,
+    }
+}
+
+/// Returns the [`ProofType`] an SP1 proof was generated with.
+pub fn proof_type_of(proof: &SP1Proof) -> ProofType {
+    match proof {
+        SP1Proof::Core(_) => ProofType::Core,
+        SP1Proof::Compressed(_) => ProofType::Compressed,
+        SP1Proof::Groth16(_) => ProofType::Groth16,
+        SP1Proof::Plonk(_) => ProofType::Plonk,
+    }
+}
+
+fn stdin(input: &Input) -> SP1Stdin {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(input);
+    stdin
+}
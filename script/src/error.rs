@@ -0,0 +1,73 @@
+use crate::Program;
+use sp1_sdk::SP1VerificationError;
+use std::io;
+use thiserror::Error;
+use vcsv_lib::ProofType;
+
+/// Errors returned by [`crate::VcsvClient`] and the proof file helpers.
+#[derive(Debug, Error)]
+pub enum VcsvError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid proof file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid public values: {0}")]
+    PublicValues(#[from] alloy_sol_types::Error),
+    #[error("program execution failed: {0:#}")]
+    Execution(anyhow::Error),
+    #[error("proof generation failed: {0:#}")]
+    Proving(anyhow::Error),
+    #[error("proof verification failed: {0}")]
+    Verification(#[from] SP1VerificationError),
+    #[error("row {row} is out of range for a file with {rows} rows")]
+    RowOutOfRange { row: usize, rows: usize },
+    #[error("csv row {row} is not valid UTF-8")]
+    InvalidCsv { row: usize },
+    #[error("csv has no data rows")]
+    EmptyCsv,
+    #[error("invalid hash {0:?}, expected 32 hex-encoded bytes")]
+    InvalidHash(String),
+    #[error("invalid signing key, expected a 32-byte hex-encoded secp256k1 private key")]
+    InvalidSigningKey,
+    #[error("invalid signature {0:?}, expected 65 hex-encoded bytes")]
+    InvalidSignature(String),
+    #[error("invalid address {0:?}")]
+    InvalidAddress(String),
+    #[error("root signature is not over the root of the file being proven")]
+    SignatureMismatch,
+    #[error("{0} cannot be proven in chunks")]
+    Unchunkable(&'static str),
+    #[error("proof commits its result rather than hiding it")]
+    ResultNotHidden,
+    #[error("invalid result {0:?}")]
+    InvalidResult(String),
+    #[error("invalid expression: {0}")]
+    InvalidExpression(String),
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
+    #[error("histograms need increasing --edges, or --buckets with a range that fits them")]
+    InvalidEdges,
+    #[error("invalid threshold {0:?}")]
+    InvalidThreshold(String),
+    #[error("range checks need a lower bound no greater than the upper bound")]
+    InvalidBounds,
+    #[error("invalid noise parameters: {0}")]
+    InvalidNoise(&'static str),
+    #[error("chunk size {0} is not a power of two")]
+    InvalidChunkSize(usize),
+    #[error("proof recursively verifies proofs of programs other than this client's")]
+    ProgramKeyMismatch,
+    #[error("only compressed proofs can be appended to, not {0:?}")]
+    NotRecursive(ProofType),
+    #[error("proof file has no Merkle frontier to append to")]
+    MissingFrontier,
+    #[error("listed changes do not match the digest committed by the diff proof")]
+    ChangesMismatch,
+    #[error("{0:?} proofs cannot be verified on their own")]
+    UnsupportedProgram(Program),
+    #[error("proof is recorded as {recorded:?} but contains a {actual:?} proof")]
+    ProofTypeMismatch {
+        recorded: ProofType,
+        actual: ProofType,
+    },
+}
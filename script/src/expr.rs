@@ -0,0 +1,323 @@
+//! Parsing the text form of [`Expr`], such as `if(region == "EU", price * 1.2, price)`.
+//!
+//! `or` binds loosest, then `and`, `not`, comparisons, `+` and `-`, `*` and `/`, and unary `-`.
+//! Columns are bare identifiers, optionally qualified by a table as in `orders.amount`, or
+//! backtick-quoted when they hold other characters or are keywords; text is double-quoted.
+
+use crate::VcsvError;
+use std::fmt;
+use vcsv_lib::{is_identifier, BinOp, Comparator, Expr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token {
+    Ident(String),
+    /// A backtick-quoted column name.
+    Quoted(String),
+    Num {
+        units: i128,
+        scale: u32,
+    },
+    Str(String),
+    Sym(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "{ident}"),
+            Token::Quoted(col) => write!(f, "`{}`", col.replace('`', "``")),
+            Token::Num { units, scale } => write!(
+                f,
+                "{}",
+                Expr::Num {
+                    units: *units,
+                    scale: *scale
+                }
+            ),
+            Token::Str(s) => write!(f, "{}", Expr::Str(s.clone())),
+            Token::Sym(sym) => write!(f, "{sym}"),
+        }
+    }
+}
+
+/// Longest first, so `<=` is not read as `<` followed by `=`.
+const SYMBOLS: [&str; 16] = [
+    "==", "!=", "<>", "<=", ">=", "<", ">", "=", "+", "-", "*", "/", "(", ")", ",", ";",
+];
+
+/// Parses an expression.
+pub fn parse_expr(s: &str) -> Result<Expr, VcsvError> {
+    let mut parser = Parser::new(s)?;
+    let expr = parser.expr()?;
+    parser.finish()?;
+    Ok(expr)
+}
+
+pub(crate) struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    pub(crate) fn new(s: &str) -> Result<Self, VcsvError> {
+        Ok(Self {
+            tokens: tokenize(s)?,
+            pos: 0,
+        })
+    }
+
+    pub(crate) fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    /// The token `n` places after the next one.
+    pub(crate) fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n)
+    }
+
+    /// Consumes the case-insensitive `keyword` if it is next.
+    pub(crate) fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found =
+            matches!(self.peek(), Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    pub(crate) fn expect_keyword(&mut self, keyword: &str) -> Result<(), VcsvError> {
+        match self.eat_keyword(keyword) {
+            true => Ok(()),
+            false => Err(self.unexpected(&keyword.to_ascii_uppercase())),
+        }
+    }
+
+    pub(crate) fn advance(&mut self) {
+        self.pos += 1;
+    }
+
+    /// A column name, bare or backtick-quoted.
+    pub(crate) fn column(&mut self) -> Result<String, VcsvError> {
+        match self.peek().cloned() {
+            Some(Token::Ident(col)) if is_identifier(&col) => {
+                self.pos += 1;
+                Ok(col)
+            }
+            Some(Token::Quoted(col)) => {
+                self.pos += 1;
+                Ok(col)
+            }
+            _ => Err(self.unexpected("a column")),
+        }
+    }
+
+    /// Consumes `sym` if it is next.
+    pub(crate) fn eat(&mut self, sym: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Sym(s)) if *s == sym);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    pub(crate) fn expect(&mut self, sym: &str) -> Result<(), VcsvError> {
+        match self.eat(sym) {
+            true => Ok(()),
+            false => Err(self.unexpected(&format!("`{sym}`"))),
+        }
+    }
+
+    /// Fails unless every token has been consumed.
+    pub(crate) fn finish(&self) -> Result<(), VcsvError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.unexpected("the end")),
+        }
+    }
+
+    pub(crate) fn unexpected(&self, expected: &str) -> VcsvError {
+        let found = match self.peek() {
+            None => "the end".to_string(),
+            Some(token) => format!("`{token}`"),
+        };
+        VcsvError::InvalidExpression(format!("expected {expected}, found {found}"))
+    }
+
+    pub(crate) fn expr(&mut self) -> Result<Expr, VcsvError> {
+        let mut left = self.and()?;
+        while self.eat_keyword("or") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, VcsvError> {
+        let mut left = self.not()?;
+        while self.eat_keyword("and") {
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, VcsvError> {
+        match self.eat_keyword("not") {
+            true => Ok(Expr::Not(Box::new(self.not()?))),
+            false => self.comparison(),
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, VcsvError> {
+        let left = self.additive()?;
+        let comparator = match self.peek() {
+            Some(Token::Sym("==" | "=")) => Comparator::Eq,
+            Some(Token::Sym("!=" | "<>")) => Comparator::Ne,
+            Some(Token::Sym("<")) => Comparator::Lt,
+            Some(Token::Sym("<=")) => Comparator::Le,
+            Some(Token::Sym(">")) => Comparator::Gt,
+            Some(Token::Sym(">=")) => Comparator::Ge,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.additive()?;
+        Ok(Expr::Cmp(comparator, Box::new(left), Box::new(right)))
+    }
+
+    fn additive(&mut self) -> Result<Expr, VcsvError> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = match () {
+                _ if self.eat("+") => BinOp::Add,
+                _ if self.eat("-") => BinOp::Sub,
+                _ => return Ok(left),
+            };
+            let right = self.multiplicative()?;
+            left = Expr::Bin(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, VcsvError> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match () {
+                _ if self.eat("*") => BinOp::Mul,
+                _ if self.eat("/") => BinOp::Div,
+                _ => return Ok(left),
+            };
+            let right = self.unary()?;
+            left = Expr::Bin(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, VcsvError> {
+        match self.eat("-") {
+            true => Ok(Expr::Neg(Box::new(self.unary()?))),
+            false => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, VcsvError> {
+        let expr = match self.peek().cloned() {
+            Some(Token::Num { units, scale }) => Expr::Num { units, scale },
+            Some(Token::Str(s)) => Expr::Str(s),
+            Some(Token::Quoted(col)) => Expr::Col(col),
+            Some(Token::Ident(name)) if name.eq_ignore_ascii_case("if") => {
+                self.pos += 1;
+                self.expect("(")?;
+                let cond = self.expr()?;
+                self.expect(",")?;
+                let then = self.expr()?;
+                self.expect(",")?;
+                let otherwise = self.expr()?;
+                self.expect(")")?;
+                return Ok(Expr::If(
+                    Box::new(cond),
+                    Box::new(then),
+                    Box::new(otherwise),
+                ));
+            }
+            Some(Token::Ident(col)) if is_identifier(&col) => Expr::Col(col),
+            Some(Token::Sym("(")) => {
+                self.pos += 1;
+                let expr = self.expr()?;
+                self.expect(")")?;
+                return Ok(expr);
+            }
+            _ => return Err(self.unexpected("a column, number or text")),
+        };
+        self.pos += 1;
+        Ok(expr)
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, VcsvError> {
+    let invalid = |message: String| VcsvError::InvalidExpression(message);
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() {
+            let (mut units, mut scale, mut fraction) = (0i128, 0u32, false);
+            while let Some(&(_, c)) = chars.peek() {
+                match c {
+                    '.' if !fraction => fraction = true,
+                    '0'..='9' => {
+                        units = units
+                            .checked_mul(10)
+                            .and_then(|units| units.checked_add(c as i128 - '0' as i128))
+                            .ok_or_else(|| invalid(format!("number at {start} is too large")))?;
+                        scale += fraction as u32;
+                    }
+                    _ => break,
+                }
+                chars.next();
+            }
+            tokens.push(Token::Num { units, scale });
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+                    break;
+                }
+                ident.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Ident(ident));
+        } else if c == '"' || c == '`' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) if c == '"' => match chars.next() {
+                        Some((_, escaped)) => text.push(escaped),
+                        None => return Err(invalid(format!("unterminated {c} at {start}"))),
+                    },
+                    // A doubled backtick is a backtick in the column name.
+                    Some((_, '`')) if c == '`' && chars.peek().is_some_and(|&(_, c)| c == '`') => {
+                        chars.next();
+                        text.push('`');
+                    }
+                    Some((_, end)) if end == c => {
+                        tokens.push(match c {
+                            '"' => Token::Str(text),
+                            _ => Token::Quoted(text),
+                        });
+                        break;
+                    }
+                    Some((_, c)) => text.push(c),
+                    None => return Err(invalid(format!("unterminated {c} at {start}"))),
+                }
+            }
+        } else {
+            let sym = SYMBOLS
+                .iter()
+                .find(|sym| s[start..].starts_with(*sym))
+                .ok_or_else(|| invalid(format!("unexpected {c:?} at {start}")))?;
+            for _ in 0..sym.len() {
+                chars.next();
+            }
+            tokens.push(Token::Sym(sym));
+        }
+    }
+    Ok(tokens)
+}
@@ -1,13 +1,34 @@
 //! VCSV Script Library
 //!
-//! This library provides functions for executing and proving the vcsv program
-
-use alloy_sol_types::SolType;
+//! This library provides functions for executing and proving the vcsv program.
+//! [`VcsvClient`] is the entry point for using it from other Rust code.
+
+mod backend;
+mod client;
+mod error;
+mod expr;
+mod query;
+mod signing;
+mod stream;
+
+pub use backend::{BackendConfig, KeySource, VcsvProver};
+pub use client::{
+    proof_mode, proof_type_of, read_input, Execution, HiddenResult, Program, ProofBundle,
+    VcsvClient,
+};
+pub use error::VcsvError;
+pub use expr::parse_expr;
+pub use query::parse_query;
+pub use signing::{
+    parse_address, parse_signature, sign_root, signing_key, verify_root_signature, RootSignature,
+};
+pub use stream::{file_root, for_each_leaf, inclusion_proof};
+
+use alloy_primitives::B256;
 use hex::decode;
 use serde::{Deserialize, Serialize};
-use sp1_sdk::{include_elf, ProverClient, SP1Stdin};
-use std::{env::set_var, fs, path::PathBuf};
-use vcsv_lib::{hash, parse_csv, Backend, Input, Op, PublicValues};
+use sp1_sdk::include_elf;
+use vcsv_lib::hash;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InclusionProof {
@@ -24,170 +45,31 @@ pub struct InclusionProofString {
 /// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
 pub const VCSV_ELF: &[u8] = include_elf!("vcsv-program");
 
-pub fn execute(file: PathBuf, op: Op, col: String) {
-    let client = ProverClient::from_env();
-    let mut stdin = SP1Stdin::new();
-
-    let csv_bytes: Vec<u8> = fs::read(file).unwrap();
-
-    let input = Input {
-        csv: csv_bytes,
-        col: col,
-        op: op,
-    };
-    stdin.write(&input);
-
-    let (output, _report) = client.execute(VCSV_ELF, &stdin).run().unwrap();
-    println!("Program executed successfully.");
-
-    let decoded = PublicValues::abi_decode(output.as_slice()).unwrap();
-    let PublicValues {
-        fileRoot,
-        op,
-        colHash,
-        n_rows,
-        result,
-        decimal_points,
-    } = decoded;
-
-    println!("fileRoot: {:?}", fileRoot);
-    println!("op: {:?}", op);
-    println!("colHash: {:?}", colHash);
-    println!("n_rows: {:?}", n_rows);
-    println!(
-        "result: {:?}",
-        result as f64 / 10_f64.powf(decimal_points as f64)
-    );
-    // println!("decimal points: {:?}", decimal_points);
-}
-
-pub fn proof(
-    file: PathBuf,
-    op: Op,
-    col: String,
-    out: PathBuf,
-    backend: Backend,
-    pkey: Option<String>,
-) {
-    match backend {
-        Backend::Cpu => set_var("SP1_PROVER", "cpu"),
-        Backend::Network => {
-            set_var("SP1_PROVER", "network");
-            set_var("NETWORK_PRIVATE_KEY", pkey.unwrap());
-        }
-    }
-    let client = ProverClient::from_env();
-    let mut stdin = SP1Stdin::new();
+/// The program proving one chunk of rows for [`VcsvClient::prove_chunked`].
+pub const VCSV_CHUNK_ELF: &[u8] = include_elf!("vcsv-chunk-program");
 
-    let csv_bytes: Vec<u8> = fs::read(file).unwrap();
+/// The program aggregating chunk proofs for [`VcsvClient::prove_chunked`].
+pub const VCSV_AGGREGATE_ELF: &[u8] = include_elf!("vcsv-aggregate-program");
 
-    let input = Input {
-        csv: csv_bytes,
-        col: col,
-        op: op,
-    };
-    stdin.write(&input);
+/// The program extending a proof with appended rows for [`VcsvClient::append`].
+pub const VCSV_APPEND_ELF: &[u8] = include_elf!("vcsv-append-program");
 
-    let (pk, _) = client.setup(VCSV_ELF);
-
-    let proof = client
-        .prove(&pk, &stdin)
-        .compressed()
-        .run()
-        .expect("failed to generate proof");
-
-    println!("Successfully generated proof!");
-
-    let proof = serde_json::to_vec_pretty(&proof).unwrap();
-    let _ = fs::write(out, proof).expect("couldn't write to file");
-}
-
-pub fn verify(file: PathBuf) {
-    let client = ProverClient::from_env();
-    let (_, vk) = client.setup(VCSV_ELF);
-
-    let proof = fs::read(file).unwrap();
-    let proof = serde_json::from_slice(&proof).unwrap();
-
-    client.verify(&proof, &vk).expect("failed to verify proof");
-    println!("Successfully verified proof!");
-}
-
-pub fn inclusion_proof(file: PathBuf, row_idx: usize) -> InclusionProof {
-    let csv = parse_csv(fs::read(file).unwrap(), None);
-
-    if row_idx >= csv.lines.len() {
-        panic!("row index out of bounds");
-    }
-    let mut i = row_idx;
-
-    let mut hashes: Vec<[u8; 32]> = Vec::new();
-
-    let mut levels: Vec<[u8; 32]> = csv
-        .lines
-        .iter()
-        .map(|line| {
-            let row = line.join(",");
-            hash(row.as_bytes())
-        })
-        .collect();
-
-    hashes.push(levels[i]);
-
-    while levels.len() > 1 {
-        let sibling_idx = if i % 2 == 0 {
-            if i + 1 < levels.len() {
-                i + 1
-            } else {
-                i
-            }
-        } else {
-            i - 1
-        };
+/// The program proving which rows differ between two files for [`VcsvClient::prove_diff`].
+pub const VCSV_DIFF_ELF: &[u8] = include_elf!("vcsv-diff-program");
 
-        hashes.push(levels[sibling_idx]);
-
-        levels = levels
-            .chunks(2)
-            .map(|pair| {
-                let left = pair[0];
-                let right = if pair.len() == 2 { pair[1] } else { pair[0] };
-
-                let mut buf = [0u8; 64];
-                buf[..32].copy_from_slice(&left);
-                buf[32..].copy_from_slice(&right);
-
-                hash(&buf)
-            })
-            .collect();
-
-        i /= 2;
-    }
-
-    InclusionProof {
-        leaf: hashes[0],
-        siblings: hashes[1..].to_vec(),
-    }
-}
-
-pub fn verify_inclusion(root: &[u8; 32], inc_proof: InclusionProofString, row: usize) -> bool {
+pub fn verify_inclusion(
+    root: &[u8; 32],
+    inc_proof: InclusionProofString,
+    row: usize,
+) -> Result<bool, VcsvError> {
     let mut i = row;
 
-    let mut leaf_bytes = [0u8; 32];
-    let decoded = decode(inc_proof.leaf.trim_start_matches("0x")).expect("invalid hex");
-    assert_eq!(decoded.len(), 32, "leaf not 32 bytes");
-
-    leaf_bytes.copy_from_slice(&decoded);
-
-    let mut running_hash = leaf_bytes;
+    let mut running_hash = parse_hash(&inc_proof.leaf)?;
 
     for s in &inc_proof.siblings {
-        let mut sib = [0u8; 32];
-        let b = decode(s.trim_start_matches("0x")).expect("invalid hex");
-        assert_eq!(b.len(), 32, "sibling not 32 bytes");
-        sib.copy_from_slice(&b);
+        let sib = parse_hash(s)?;
 
-        if i % 2 == 0 {
+        if i.is_multiple_of(2) {
             let mut buf = [0u8; 64];
             buf[..32].copy_from_slice(&running_hash);
             buf[32..].copy_from_slice(&sib);
@@ -204,5 +86,18 @@ pub fn verify_inclusion(root: &[u8; 32], inc_proof: InclusionProofString, row: u
         i /= 2;
     }
 
-    running_hash == *root
+    Ok(running_hash == *root)
+}
+
+/// A fresh random nonce for committing to a hidden result.
+pub fn random_nonce() -> [u8; 32] {
+    B256::random().0
+}
+
+/// Parses a 32-byte hash written as hex, with or without a `0x` prefix.
+pub fn parse_hash(s: &str) -> Result<[u8; 32], VcsvError> {
+    let bytes = decode(s.trim_start_matches("0x")).map_err(|_| VcsvError::InvalidHash(s.into()))?;
+    bytes
+        .try_into()
+        .map_err(|_| VcsvError::InvalidHash(s.into()))
 }
@@ -0,0 +1,114 @@
+//! Parsing the SQL dialect of [`Query`]:
+//!
+//! ```text
+//! SELECT key | COUNT(*) | SUM(expr) | AVG(expr) | MIN(expr) | MAX(expr), ...
+//! FROM table [JOIN table ON key = key ...] [WHERE condition] [GROUP BY key, ...]
+//! ```
+//!
+//! Keywords are case-insensitive, and expressions are those of [`parse_expr`](crate::parse_expr).
+//! The `FROM` table is the file being proven, and each `JOIN` table is another file, given in the
+//! same order. A query without joins is canonicalized to `FROM data`, whatever its table was
+//! called.
+
+use crate::expr::{Parser, Token};
+use crate::VcsvError;
+use vcsv_lib::{Aggregate, Join, Query, Selected};
+
+/// Parses a query.
+pub fn parse_query(sql: &str) -> Result<Query, VcsvError> {
+    let query = parse(sql).map_err(|e| match e {
+        VcsvError::InvalidExpression(message) => VcsvError::InvalidQuery(message),
+        e => e,
+    })?;
+
+    query.validate().map_err(VcsvError::InvalidQuery)?;
+    let mut tables = vec![&query.from];
+    for join in &query.joins {
+        if tables.contains(&&join.table) {
+            return Err(VcsvError::InvalidQuery(format!(
+                "table {} appears twice",
+                join.table
+            )));
+        }
+        tables.push(&join.table);
+    }
+    Ok(query)
+}
+
+fn parse(sql: &str) -> Result<Query, VcsvError> {
+    let mut parser = Parser::new(sql)?;
+    parser.expect_keyword("select")?;
+    let mut select = vec![selected(&mut parser)?];
+    while parser.eat(",") {
+        select.push(selected(&mut parser)?);
+    }
+
+    parser.expect_keyword("from")?;
+    let mut from = parser.column()?;
+    let mut joins = Vec::new();
+    while parser.eat_keyword("join") {
+        let table = parser.column()?;
+        parser.expect_keyword("on")?;
+        let mut left = parser.column()?;
+        parser.expect("=")?;
+        let mut right = parser.column()?;
+        // The joined table's key may come first.
+        if left.starts_with(&format!("{table}.")) {
+            std::mem::swap(&mut left, &mut right);
+        }
+        joins.push(Join { table, left, right });
+    }
+    if joins.is_empty() {
+        from = "data".to_string();
+    }
+    let filter = match parser.eat_keyword("where") {
+        true => Some(parser.expr()?),
+        false => None,
+    };
+    let mut group_by = Vec::new();
+    if parser.eat_keyword("group") {
+        parser.expect_keyword("by")?;
+        group_by.push(parser.column()?);
+        while parser.eat(",") {
+            group_by.push(parser.column()?);
+        }
+    }
+    parser.eat(";");
+    parser.finish()?;
+
+    Ok(Query {
+        select,
+        from,
+        joins,
+        filter,
+        group_by,
+    })
+}
+
+fn selected(parser: &mut Parser) -> Result<Selected, VcsvError> {
+    let aggregate = match (parser.peek(), parser.peek_nth(1)) {
+        (Some(Token::Ident(name)), Some(Token::Sym("("))) => {
+            match name.to_ascii_lowercase().as_str() {
+                "count" => Aggregate::Count,
+                "sum" => Aggregate::Sum,
+                "avg" => Aggregate::Avg,
+                "min" => Aggregate::Min,
+                "max" => Aggregate::Max,
+                _ => return Err(parser.unexpected("COUNT, SUM, AVG, MIN or MAX")),
+            }
+        }
+        _ => return Ok(Selected::Key(parser.column()?)),
+    };
+
+    parser.advance();
+    parser.expect("(")?;
+    let expr = match aggregate {
+        Aggregate::Count => {
+            parser.expect("*")?;
+            None
+        }
+        _ => Some(parser.expr()?),
+    };
+    parser.expect(")")?;
+    Ok(Selected::Agg(aggregate, expr))
+}
@@ -0,0 +1,68 @@
+//! Signing a file root as the data provider, and checking such signatures.
+
+use crate::{parse_hash, VcsvError};
+use alloy_primitives::Address;
+use hex::decode;
+use k256::ecdsa::SigningKey;
+use serde::{Deserialize, Serialize};
+use vcsv_lib::{address, eip191_hash, recover_signer};
+
+/// A provider's signature over a file root, as written by `vcsv sign-root`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RootSignature {
+    pub root: String,
+    /// The checksummed Ethereum address of the signing key.
+    pub signer: String,
+    /// The 65-byte `r || s || v` signature, hex encoded.
+    pub signature: String,
+}
+
+/// Parses a secp256k1 private key written as hex, with or without a `0x` prefix.
+pub fn signing_key(s: &str) -> Result<SigningKey, VcsvError> {
+    let bytes = decode(s.trim_start_matches("0x")).map_err(|_| VcsvError::InvalidSigningKey)?;
+    SigningKey::from_slice(&bytes).map_err(|_| VcsvError::InvalidSigningKey)
+}
+
+/// Signs a root with EIP-191 `personal_sign`, so the signature can also be checked on-chain
+/// with `ecrecover`.
+pub fn sign_root(root: &[u8; 32], key: &SigningKey) -> RootSignature {
+    let (signature, recovery_id) = key
+        .sign_prehash_recoverable(&eip191_hash(root))
+        .expect("signing a 32-byte prehash cannot fail");
+
+    let mut bytes = signature.to_bytes().to_vec();
+    bytes.push(27 + recovery_id.to_byte());
+
+    RootSignature {
+        root: format!("0x{}", hex::encode(root)),
+        signer: address(key.verifying_key()).to_checksum(None),
+        signature: format!("0x{}", hex::encode(bytes)),
+    }
+}
+
+/// Checks that `signature` is over `root` and was made by the key of its `signer`.
+pub fn verify_root_signature(
+    root: &[u8; 32],
+    signature: &RootSignature,
+) -> Result<bool, VcsvError> {
+    let signer = parse_address(&signature.signer)?;
+    if parse_hash(&signature.root)? != *root {
+        return Ok(false);
+    }
+
+    Ok(recover_signer(root, &parse_signature(&signature.signature)?) == Some(signer))
+}
+
+/// Parses an Ethereum address written as hex.
+pub fn parse_address(s: &str) -> Result<Address, VcsvError> {
+    s.parse().map_err(|_| VcsvError::InvalidAddress(s.into()))
+}
+
+/// Parses a 65-byte signature written as hex, with or without a `0x` prefix.
+pub fn parse_signature(s: &str) -> Result<[u8; 65], VcsvError> {
+    let bytes =
+        decode(s.trim_start_matches("0x")).map_err(|_| VcsvError::InvalidSignature(s.into()))?;
+    bytes
+        .try_into()
+        .map_err(|_| VcsvError::InvalidSignature(s.into()))
+}
@@ -0,0 +1,110 @@
+//! Reads CSV files one line at a time, so hashing a file never holds more than its longest row
+//! and the O(log n) Merkle frontier in memory.
+
+use crate::{InclusionProof, VcsvError};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    str,
+};
+use vcsv_lib::{hash_row, MerkleFrontier};
+
+/// Calls `f` with the leaf hash of every data row, in order.
+///
+/// Rows are delimited exactly as `parse_csv` delimits them: the first line is the header, and
+/// empty lines are skipped.
+pub fn for_each_leaf(
+    mut reader: impl BufRead,
+    mut f: impl FnMut([u8; 32]),
+) -> Result<(), VcsvError> {
+    let mut line = Vec::new();
+
+    // The header is not part of the tree.
+    reader.read_until(b'\n', &mut line)?;
+
+    let mut row = 0;
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let text = str::from_utf8(&line).map_err(|_| VcsvError::InvalidCsv { row })?;
+        f(hash_row(text));
+        row += 1;
+    }
+}
+
+/// Computes the Merkle root the program commits to as `fileRoot`, without loading the file.
+pub fn file_root(file: impl AsRef<Path>) -> Result<[u8; 32], VcsvError> {
+    read_frontier(BufReader::new(File::open(file)?))?
+        .root()
+        .ok_or(VcsvError::EmptyCsv)
+}
+
+/// The Merkle frontier after every row of a CSV, read one line at a time.
+pub(crate) fn read_frontier(reader: impl BufRead) -> Result<MerkleFrontier, VcsvError> {
+    let mut frontier = MerkleFrontier::new();
+    for_each_leaf(reader, |leaf| frontier.push(leaf))?;
+    Ok(frontier)
+}
+
+/// Builds the inclusion proof for a data row while streaming the file.
+pub fn inclusion_proof(
+    file: impl AsRef<Path>,
+    row_idx: usize,
+) -> Result<InclusionProof, VcsvError> {
+    let row = row_idx as u64;
+    // The node on the row's path and its sibling, per level.
+    let mut path: Vec<Option<[u8; 32]>> = Vec::new();
+    let mut siblings: Vec<Option<[u8; 32]>> = Vec::new();
+    let mut visit = |level: u32, index: u64, node: &[u8; 32]| {
+        let level = level as usize;
+        let target = row >> level;
+        let slot = if index == target {
+            &mut path
+        } else if index == target ^ 1 {
+            &mut siblings
+        } else {
+            return;
+        };
+        if slot.len() <= level {
+            slot.resize(level + 1, None);
+        }
+        slot[level] = Some(*node);
+    };
+
+    let mut frontier = MerkleFrontier::new();
+    for_each_leaf(BufReader::new(File::open(file)?), |leaf| {
+        frontier.push_with(leaf, &mut visit)
+    })?;
+
+    if row >= frontier.leaves() {
+        return Err(VcsvError::RowOutOfRange {
+            row: row_idx,
+            rows: frontier.leaves() as usize,
+        });
+    }
+    frontier.root_with(&mut visit);
+
+    // The root is the last node on the path; every level below it has a sibling, which is the
+    // node itself when it was the odd one out.
+    siblings.resize(path.len() - 1, None);
+    let siblings = siblings
+        .iter()
+        .zip(&path)
+        .map(|(sibling, node)| sibling.or(*node).unwrap())
+        .collect();
+
+    Ok(InclusionProof {
+        leaf: path[0].unwrap(),
+        siblings,
+    })
+}
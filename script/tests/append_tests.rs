@@ -0,0 +1,122 @@
+mod common;
+
+use std::fs;
+
+use alloy_sol_types::SolType;
+use common::{file, tmpdir};
+use vcsv_lib::{
+    append, frontier, parse_csv, run, AppendInput, AppendValues, Input, Op, ProgramKeys, ProofType,
+    PublicValues,
+};
+use vcsv_script::{read_input, BackendConfig, Program, ProofBundle, VcsvClient};
+
+fn keys() -> ProgramKeys {
+    ProgramKeys {
+        vcsv: [1; 8],
+        chunk: [2; 8],
+        aggregate: [3; 8],
+        append: [4; 8],
+    }
+}
+
+fn input(csv: String, op: Op) -> Input {
+    Input {
+        csv: csv.into_bytes(),
+        col: "price".to_string(),
+        op,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn appends_match_proving_the_whole_file() {
+    let first = file(0..7);
+    let prev = run(input(first.clone(), Op::Sum));
+
+    // Extend a sum proof from the vcsv program.
+    let (public, values, next_frontier) = append(AppendInput {
+        keys: keys(),
+        prev_vkey: keys().vcsv,
+        prev_public_values: PublicValues::abi_encode(&prev),
+        frontier: frontier(&parse_csv(first.into_bytes(), None)),
+        csv: file(7..12).into_bytes(),
+        col: "price".to_string(),
+        op: Op::Mean,
+        signature: None,
+        min_rows: 0,
+    });
+    let whole = file(0..12);
+    assert_eq!(public, run(input(whole.clone(), Op::Mean)));
+    assert_eq!(values.sum, run(input(whole, Op::Sum)).result);
+
+    // Extend the append proof itself.
+    let mut prev_public_values = PublicValues::abi_encode(&public);
+    prev_public_values.extend_from_slice(&AppendValues::abi_encode(&values));
+    let (public, _, _) = append(AppendInput {
+        keys: keys(),
+        prev_vkey: keys().append,
+        prev_public_values,
+        frontier: next_frontier,
+        csv: file(12..30).into_bytes(),
+        col: "price".to_string(),
+        op: Op::Sum,
+        signature: None,
+        min_rows: 0,
+    });
+    assert_eq!(public, run(input(file(0..30), Op::Sum)));
+}
+
+#[test]
+#[should_panic(expected = "frontier does not match the previous root")]
+fn append_rejects_a_different_frontier() {
+    let prev = run(input(file(0..7), Op::Sum));
+
+    append(AppendInput {
+        keys: keys(),
+        prev_vkey: keys().vcsv,
+        prev_public_values: PublicValues::abi_encode(&prev),
+        frontier: frontier(&parse_csv(file(1..8).into_bytes(), None)),
+        csv: file(7..12).into_bytes(),
+        col: "price".to_string(),
+        op: Op::Sum,
+        signature: None,
+        min_rows: 0,
+    });
+}
+
+#[test]
+#[cfg_attr(not(guest_programs), ignore = "the guest programs were not built")]
+fn mock_append_twice_and_verify() {
+    let dir = tmpdir();
+    let client = VcsvClient::new(BackendConfig::Mock);
+
+    let path = dir.join("day1.csv");
+    fs::write(&path, file(0..5)).unwrap();
+    let mut bundle = client
+        .prove(
+            &read_input(&path, Op::Sum, "price").unwrap(),
+            ProofType::Compressed,
+        )
+        .unwrap();
+
+    for (day, range) in [(2, 5..9), (3, 9..20)] {
+        let path = dir.join(format!("day{day}.csv"));
+        fs::write(&path, file(range)).unwrap();
+
+        let out = dir.join(format!("day{day}.json"));
+        client
+            .append(
+                &bundle,
+                &read_input(&path, Op::Sum, "price").unwrap(),
+                ProofType::Compressed,
+            )
+            .unwrap()
+            .write(&out)
+            .unwrap();
+        bundle = ProofBundle::read(&out).unwrap();
+        assert_eq!(bundle.program, Program::Append);
+    }
+
+    let public = client.verify(&bundle).unwrap();
+    assert_eq!(public, run(input(file(0..20), Op::Sum)));
+}
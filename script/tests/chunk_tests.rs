@@ -0,0 +1,161 @@
+mod common;
+
+use std::fs;
+
+use alloy_sol_types::SolType;
+use common::{padded_csv, tmpdir};
+use vcsv_lib::{
+    aggregate, chunk_values, median_pivots, run, split_chunks, AggregateInput, ChunkInput,
+    ChunkValues, Input, Op, ProofType, PublicValues,
+};
+use vcsv_script::{read_input, BackendConfig, Program, ProofBundle, VcsvClient};
+
+/// Runs the chunk and aggregation programs' logic on the host, without proofs.
+fn aggregate_on_host(input: &Input, chunk_rows: usize) -> PublicValues {
+    let split = split_chunks(&input.csv, chunk_rows);
+    let pivots = matches!(input.op, Op::Median).then(|| median_pivots(&split, &input.col));
+    aggregate_with_pivots(input, chunk_rows, pivots)
+}
+
+fn aggregate_with_pivots(
+    input: &Input,
+    chunk_rows: usize,
+    pivots: Option<[i128; 2]>,
+) -> PublicValues {
+    let mut chunks = Vec::new();
+    for (first_row, csv) in split_chunks(&input.csv, chunk_rows) {
+        let chunk = chunk_values(ChunkInput {
+            csv,
+            col: input.col.clone(),
+            op: input.op.clone(),
+            first_row,
+            pivots,
+        });
+        // Round-trip through the committed bytes like the aggregation program does.
+        chunks.push(ChunkValues::abi_decode(&ChunkValues::abi_encode(&chunk)).unwrap());
+    }
+
+    let aggregate_input = AggregateInput {
+        chunk_vkey: [0; 8],
+        chunk_height: chunk_rows.trailing_zeros() as u8,
+        col: input.col.clone(),
+        op: input.op.clone(),
+        chunks: Vec::new(),
+        signature: None,
+        min_rows: 0,
+        claim: None,
+        result_nonce: None,
+    };
+    aggregate(&aggregate_input, &chunks)
+}
+
+#[test]
+fn aggregate_matches_single_shot() {
+    for rows in [1, 2, 3, 7, 8, 9, 16, 33] {
+        for op in [Op::Sum, Op::Mean, Op::Median] {
+            let input = Input {
+                csv: padded_csv(rows),
+                col: "price".to_string(),
+                op,
+                ..Default::default()
+            };
+            let expected = run(input.clone());
+
+            for chunk_rows in [1, 2, 4, 8, 64] {
+                let public = aggregate_on_host(&input, chunk_rows);
+                assert_eq!(
+                    PublicValues::abi_encode(&public),
+                    PublicValues::abi_encode(&expected),
+                    "{rows} rows in chunks of {chunk_rows}, {:?}",
+                    input.op
+                );
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "chunks are not contiguous")]
+fn aggregate_rejects_missing_chunk() {
+    let input = Input {
+        csv: padded_csv(12),
+        col: "price".to_string(),
+        op: Op::Sum,
+        ..Default::default()
+    };
+
+    let chunks: Vec<ChunkValues> = split_chunks(&input.csv, 4)
+        .into_iter()
+        .map(|(first_row, csv)| {
+            chunk_values(ChunkInput {
+                csv,
+                col: input.col.clone(),
+                op: Op::Sum,
+                first_row,
+                pivots: None,
+            })
+        })
+        .enumerate()
+        .filter(|(i, _)| *i != 1)
+        .map(|(_, chunk)| chunk)
+        .collect();
+
+    aggregate(
+        &AggregateInput {
+            chunk_vkey: [0; 8],
+            chunk_height: 2,
+            col: input.col,
+            op: Op::Sum,
+            chunks: Vec::new(),
+            signature: None,
+            min_rows: 0,
+            claim: None,
+            result_nonce: None,
+        },
+        &chunks,
+    );
+}
+
+#[test]
+#[should_panic(expected = "the pivots are not the middle values")]
+fn aggregate_rejects_median_pivots_off_the_middle() {
+    let input = Input {
+        csv: padded_csv(9),
+        col: "price".to_string(),
+        op: Op::Median,
+        ..Default::default()
+    };
+    let [low, high] = median_pivots(&split_chunks(&input.csv, 4), &input.col);
+    assert_eq!(low, high);
+
+    aggregate_with_pivots(&input, 4, Some([low + 1, high + 1]));
+}
+
+#[test]
+#[cfg_attr(not(guest_programs), ignore = "the guest programs were not built")]
+fn mock_prove_chunked_and_verify() {
+    let dir = tmpdir();
+    let path = dir.join("data.csv");
+    fs::write(&path, padded_csv(10)).unwrap();
+
+    let client = VcsvClient::new(BackendConfig::Mock);
+    let input = read_input(&path, Op::Median, "price").unwrap();
+    let out = dir.join("proof.json");
+
+    client
+        .prove_chunked(&input, 4, ProofType::Compressed)
+        .unwrap()
+        .write(&out)
+        .unwrap();
+
+    let bundle = ProofBundle::read(&out).unwrap();
+    assert_eq!(bundle.program, Program::Aggregate);
+
+    let public = client.verify(&bundle).unwrap();
+    assert_eq!(
+        PublicValues::abi_encode(&public),
+        PublicValues::abi_encode(&run(input.clone()))
+    );
+
+    assert!(client.prove_chunked(&input, 3, ProofType::Core).is_err());
+}
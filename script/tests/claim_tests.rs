@@ -0,0 +1,52 @@
+use vcsv_lib::{comparator_to_u8, run, Claim, Comparator, Input, Op};
+
+fn input(op: Op, claim: Option<Claim>) -> Input {
+    Input {
+        csv: b"id,revenue\n1,400000\n2,350000\n3,300000\n".to_vec(),
+        col: "revenue".to_string(),
+        op,
+        claim,
+        ..Default::default()
+    }
+}
+
+fn claim(comparator: Comparator, threshold: i128) -> Option<Claim> {
+    Some(Claim {
+        comparator,
+        threshold,
+    })
+}
+
+#[test]
+fn claim_hides_the_result() {
+    let exact = run(input(Op::Sum, None));
+    assert_eq!(exact.result, 1_050_000);
+    assert_eq!(exact.comparator, 0);
+
+    let public = run(input(Op::Sum, claim(Comparator::Gt, 1_000_000)));
+    assert_eq!(public.result, 1);
+    assert_eq!(public.comparator, comparator_to_u8(Comparator::Gt));
+    assert_eq!(public.threshold, 1_000_000);
+    assert_eq!(public.n_rows, exact.n_rows);
+
+    assert_eq!(
+        run(input(Op::Sum, claim(Comparator::Le, 1_000_000))).result,
+        0
+    );
+}
+
+#[test]
+fn claim_compares_at_the_result_scale() {
+    // The mean is 350000.000, committed as 350000000 with 3 decimal points.
+    for (comparator, holds) in [
+        (Comparator::Ge, 1),
+        (Comparator::Eq, 1),
+        (Comparator::Gt, 0),
+        (Comparator::Ne, 0),
+        (Comparator::Lt, 0),
+    ] {
+        let public = run(input(Op::Mean, claim(comparator, 350_000_000)));
+        assert_eq!(public.result, holds, "{comparator:?}");
+        assert_eq!(public.decimal_points, 3);
+    }
+}
@@ -0,0 +1,27 @@
+use vcsv_lib::{run, Input, Op};
+
+fn input(op: Op, min_rows: u64) -> Input {
+    Input {
+        csv: b"id,salary\n1,52000\n2,61000\n3,58000\n".to_vec(),
+        col: "salary".to_string(),
+        op,
+        min_rows,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn min_rows_is_committed() {
+    let public = run(input(Op::Median, 3));
+    assert_eq!(public.n_rows, 3);
+    assert_eq!(public.min_rows, 3);
+    assert_eq!(public.result, 58_000_000);
+
+    assert_eq!(run(input(Op::Median, 0)).min_rows, 0);
+}
+
+#[test]
+#[should_panic(expected = "cohort of 3 rows is below the minimum of 5")]
+fn small_cohorts_fail() {
+    run(input(Op::Mean, 5));
+}
@@ -0,0 +1,59 @@
+//! Helpers shared by the integration tests. Each test binary uses only some of them.
+#![allow(dead_code)]
+
+use std::{
+    env, fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const HEADER: &str = "id,price,qty\n";
+
+/// A fresh directory under the system temp dir.
+pub fn tmpdir() -> PathBuf {
+    let nonce = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = env::temp_dir().join(format!("vcsv_test_{nonce}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Writes `contents` to `data.csv` in a fresh directory.
+pub fn write_csv(contents: &str) -> PathBuf {
+    let path = tmpdir().join("data.csv");
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+/// Generated rows under [`HEADER`], numbered by `range`.
+pub fn rows(range: std::ops::Range<usize>) -> String {
+    range
+        .map(|i| format!("{i},{},{}\n", (i * 37) % 101, i % 3))
+        .collect()
+}
+
+/// [`HEADER`] followed by the generated rows numbered by `range`.
+pub fn file(range: std::ops::Range<usize>) -> String {
+    format!("{HEADER}{}", rows(range))
+}
+
+/// A file of `rows` generated rows.
+pub fn csv(rows: usize) -> Vec<u8> {
+    file(0..rows).into_bytes()
+}
+
+/// A file of `rows` generated rows with negative prices and whitespace around cells, which must
+/// not change any result.
+pub fn padded_csv(rows: usize) -> Vec<u8> {
+    let mut csv = String::from("id, price ,qty\n");
+    for i in 0..rows {
+        csv.push_str(&format!(
+            "{i}, {} ,{}\n",
+            ((i * 37) % 101) as i64 - 50,
+            i % 3
+        ));
+    }
+    csv.into_bytes()
+}
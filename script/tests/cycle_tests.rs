@@ -0,0 +1,41 @@
+mod common;
+
+use common::padded_csv;
+use vcsv_lib::{Input, Op};
+use vcsv_script::{BackendConfig, VcsvClient};
+
+/// Rows in the file the budgets are measured on.
+const ROWS: u64 = 1000;
+
+/// Cycles the program may spend before reading any row: reading the input and committing.
+const FIXED_CYCLES: u64 = 1_000_000;
+
+/// Cycles each op may spend per row, including parsing the row and hashing it into the file's
+/// Merkle tree. Copying every cell out of the input buffer again would exceed these.
+const ROW_CYCLES: [(Op, u64); 3] = [(Op::Sum, 6_000), (Op::Mean, 6_000), (Op::Median, 8_000)];
+
+#[test]
+#[cfg_attr(not(guest_programs), ignore = "the guest programs were not built")]
+fn ops_stay_within_their_cycle_budget() {
+    let client = VcsvClient::new(BackendConfig::Cpu);
+
+    for (op, row_cycles) in ROW_CYCLES {
+        let input = Input {
+            csv: padded_csv(ROWS as usize),
+            col: "price".to_string(),
+            op: op.clone(),
+            ..Default::default()
+        };
+        let cycles = client
+            .execute(&input)
+            .unwrap()
+            .report
+            .total_instruction_count();
+
+        let budget = FIXED_CYCLES + row_cycles * ROWS;
+        assert!(
+            cycles <= budget,
+            "{op:?} took {cycles} cycles over {ROWS} rows, above its budget of {budget}"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::tmpdir;
+use vcsv_lib::{
+    changes_digest, diff, merkelize, parse_csv, ChangeKind, DiffInput, ProofType, RowChange,
+};
+use vcsv_script::{BackendConfig, ProofBundle, VcsvClient, VcsvError};
+
+const OLD: &str = "id,price,qty\n1,120,3\n2,80,1\n3,200,5\n4,50,2\n";
+// Row 1 is corrected, row 2 only gains whitespace, row 3 is dropped.
+const NEW: &str = "id,price,qty\n1,120,3\n2,85,1\n3, 200 ,5\n";
+
+fn change(row: u64, kind: ChangeKind) -> RowChange {
+    RowChange { row, kind }
+}
+
+#[test]
+fn diff_lists_changed_rows() {
+    let (values, changes) = diff(DiffInput {
+        old: OLD.into(),
+        new: NEW.into(),
+    });
+
+    assert_eq!(
+        changes,
+        [
+            change(1, ChangeKind::Modified),
+            change(3, ChangeKind::Removed)
+        ]
+    );
+    assert_eq!(values.oldRoot, merkelize(&parse_csv(OLD.into(), None)));
+    assert_eq!(values.newRoot, merkelize(&parse_csv(NEW.into(), None)));
+    assert_eq!((values.oldRows, values.newRows), (4, 3));
+    assert_eq!((values.added, values.removed, values.modified), (0, 1, 1));
+    assert_eq!(values.changesDigest, changes_digest(&changes));
+
+    let (values, changes) = diff(DiffInput {
+        old: NEW.into(),
+        new: OLD.into(),
+    });
+    assert_eq!(
+        changes,
+        [
+            change(1, ChangeKind::Modified),
+            change(3, ChangeKind::Added)
+        ]
+    );
+    assert_eq!(values.added, 1);
+}
+
+#[test]
+#[cfg_attr(not(guest_programs), ignore = "the guest programs were not built")]
+fn mock_diff_proof_rejects_edited_changes() {
+    let dir = tmpdir();
+    let out = dir.join("diff.json");
+    let client = VcsvClient::new(BackendConfig::Mock);
+
+    client
+        .prove_diff(OLD.into(), NEW.into(), ProofType::Compressed)
+        .unwrap()
+        .write(&out)
+        .unwrap();
+
+    let mut bundle = ProofBundle::read(&out).unwrap();
+    let values = client.verify_diff(&bundle).unwrap();
+    assert_eq!(values.modified, 1);
+
+    // Hiding the modified row must not verify.
+    bundle.changes = Some(vec![change(3, ChangeKind::Removed)]);
+    assert!(matches!(
+        client.verify_diff(&bundle),
+        Err(VcsvError::ChangesMismatch)
+    ));
+}
@@ -0,0 +1,113 @@
+use vcsv_lib::{hash, run, BinOp, Expr, Input, Op};
+use vcsv_script::{parse_expr, VcsvError};
+
+const CSV: &[u8] = b"region,price,qty,discount\nEU,100,2,5\nUS,50,3,0\nEU,10,1,1\n";
+
+fn input(op: Op, expr: &str) -> Input {
+    Input {
+        csv: CSV.to_vec(),
+        col: String::new(),
+        op,
+        expr: Some(parse_expr(expr).unwrap()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn expressions_have_a_canonical_form() {
+    let expr = parse_expr("price*qty - discount").unwrap();
+    assert_eq!(expr.to_string(), "((price * qty) - discount)");
+
+    let expr = parse_expr(r#"IF(region = "EU", price * 1.20, -`unit price`)"#).unwrap();
+    assert_eq!(
+        expr.to_string(),
+        r#"if((region == "EU"), (price * 1.20), (-`unit price`))"#
+    );
+    assert_eq!(parse_expr(&expr.to_string()).unwrap(), expr);
+
+    let expr = parse_expr("a or b and not c > 1").unwrap();
+    assert_eq!(expr.to_string(), "(a or (b and (not (c > 1))))");
+}
+
+#[test]
+fn backticks_in_column_names_are_escaped() {
+    let expr = parse_expr("`p q` + `r s`` + ``t u`").unwrap();
+    assert_eq!(
+        expr,
+        Expr::Bin(
+            BinOp::Add,
+            Box::new(Expr::Col("p q".to_string())),
+            Box::new(Expr::Col("r s` + `t u".to_string()))
+        )
+    );
+    assert_eq!(expr.to_string(), "(`p q` + `r s`` + ``t u`)");
+    assert_eq!(parse_expr(&expr.to_string()).unwrap(), expr);
+
+    // Splitting the same text differently between columns gives another canonical form.
+    let other = Expr::Bin(
+        BinOp::Add,
+        Box::new(Expr::Col("p q` + `r s".to_string())),
+        Box::new(Expr::Col("t u".to_string())),
+    );
+    assert_ne!(other.to_string(), expr.to_string());
+    assert_ne!(other.hash(), expr.hash());
+}
+
+#[test]
+fn expressions_are_aggregated_per_row() {
+    let public = run(input(Op::Sum, "price * qty - discount"));
+    assert_eq!(public.n_rows, 3);
+    assert_eq!(public.result, 354);
+    assert_eq!(public.decimal_points, 0);
+    assert_eq!(public.colHash, [0u8; 32]);
+    assert_eq!(public.exprHash, hash(b"((price * qty) - discount)"));
+
+    // 120.0 + 50 + 12.0, at the scale of the wider branch.
+    let public = run(input(Op::Sum, r#"if(region == "EU", price * 1.2, price)"#));
+    assert_eq!((public.result, public.decimal_points), (1_820, 1));
+
+    let public = run(input(Op::Mean, r#"if(region == "EU", price * 1.2, price)"#));
+    assert_eq!((public.result, public.decimal_points), (606_667, 4));
+
+    // 50 + 16.667 + 10, with quotients rounded to three decimals.
+    let public = run(input(Op::Sum, "price / qty"));
+    assert_eq!((public.result, public.decimal_points), (76_667, 3));
+
+    let public = run(input(
+        Op::Sum,
+        r#"qty > 1 and not region == "US" or discount == 1"#,
+    ));
+    assert_eq!(public.result, 2);
+
+    let public = run(input(Op::Median, "qty > 1"));
+    assert_eq!((public.result, public.decimal_points), (1_000, 3));
+}
+
+#[test]
+fn invalid_expressions_are_rejected() {
+    for expr in [
+        "price *",
+        "(price",
+        "price qty",
+        r#""EU"#,
+        "price # 2",
+        "if(qty, 1)",
+    ] {
+        assert!(
+            matches!(parse_expr(expr), Err(VcsvError::InvalidExpression(_))),
+            "{expr}"
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "text can only be compared with == and !=")]
+fn text_is_only_compared_for_equality() {
+    run(input(Op::Sum, r#"region < "EU""#));
+}
+
+#[test]
+#[should_panic(expected = "expressions can only be summed, averaged or have their median taken")]
+fn expressions_support_sums_means_and_medians() {
+    run(input(Op::RangeCheck, "price"));
+}
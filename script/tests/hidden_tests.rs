@@ -0,0 +1,64 @@
+use vcsv_lib::{result_commitment, run, Input, Op, ProofType};
+use vcsv_script::{BackendConfig, VcsvClient};
+
+fn input(op: Op, result_nonce: Option<[u8; 32]>) -> Input {
+    Input {
+        csv: b"id,bid\n1,1200\n2,950\n3,1430\n".to_vec(),
+        col: "bid".to_string(),
+        op,
+        result_nonce,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn hidden_result_is_committed_with_its_nonce() {
+    let nonce = [9u8; 32];
+    for op in [Op::Sum, Op::Mean, Op::Median] {
+        let exact = run(input(op.clone(), None));
+        assert_eq!(exact.resultCommitment, [0u8; 32]);
+
+        let hidden = run(input(op, Some(nonce)));
+        assert_eq!(hidden.result, 0);
+        assert_eq!(
+            hidden.resultCommitment,
+            result_commitment(exact.result, &nonce)
+        );
+        assert_eq!(hidden.decimal_points, exact.decimal_points);
+
+        // Neither another result nor another nonce opens the commitment.
+        assert_ne!(
+            hidden.resultCommitment,
+            result_commitment(exact.result + 1, &nonce)
+        );
+        assert_ne!(
+            hidden.resultCommitment,
+            result_commitment(exact.result, &[8u8; 32])
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(not(guest_programs), ignore = "the guest programs were not built")]
+fn proving_returns_the_hidden_result() {
+    let client = VcsvClient::new(BackendConfig::Mock);
+    let nonce = [9u8; 32];
+    for op in [Op::Sum, Op::Median] {
+        let exact = run(input(op.clone(), None)).result;
+        let input = input(op, Some(nonce));
+
+        for bundle in [
+            client.prove(&input, ProofType::Core).unwrap(),
+            client
+                .prove_chunked(&input, 2, ProofType::Compressed)
+                .unwrap(),
+        ] {
+            let hidden = bundle.hidden_result.as_ref().unwrap();
+            assert_eq!((hidden.result, hidden.nonce), (exact, nonce));
+            assert_eq!(
+                bundle.public_values().unwrap().resultCommitment,
+                result_commitment(hidden.result, &hidden.nonce)
+            );
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use vcsv_lib::{equal_width_edges, op_to_u8, run, run_with_table, Input, Op, Table};
+
+fn input(edges: Vec<i128>) -> Input {
+    Input {
+        csv: b"id,age\n1,34\n2,17\n3,121\n4,18\n5,120\n6,65\n".to_vec(),
+        col: "age".to_string(),
+        op: Op::Histogram,
+        edges,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn histogram_counts_buckets() {
+    let (public, table) = run_with_table(input(vec![18, 65, 120]));
+    let Some(Table::Histogram(histogram)) = table else {
+        panic!("no histogram")
+    };
+    assert_eq!(public.op, op_to_u8(Op::Histogram));
+    assert_eq!(public.n_rows, 6);
+    // 17 and 121 fall outside; the top edge belongs to the last bucket.
+    assert_eq!(public.result, 2);
+    assert_eq!(histogram.edges, vec![18, 65, 120]);
+    assert_eq!(histogram.counts, vec![2, 2]);
+}
+
+#[test]
+fn equal_width_buckets() {
+    let edges = equal_width_edges(0, 120, 4);
+    assert_eq!(edges, vec![0, 30, 60, 90, 120]);
+
+    let (public, table) = run_with_table(input(edges));
+    let Some(Table::Histogram(histogram)) = table else {
+        panic!("no histogram")
+    };
+    assert_eq!(public.result, 1);
+    assert_eq!(histogram.counts, vec![2, 1, 1, 1]);
+}
+
+#[test]
+fn other_ops_commit_no_histogram() {
+    let (_, table) = run_with_table(Input {
+        op: Op::Sum,
+        ..input(Vec::new())
+    });
+    assert!(table.is_none());
+}
+
+#[test]
+#[should_panic(expected = "histogram edges must be increasing")]
+fn edges_must_increase() {
+    run(input(vec![18, 18, 65]));
+}
+
+#[test]
+#[should_panic(expected = "a histogram needs at least two edges")]
+fn histogram_requires_edges() {
+    run(input(Vec::new()));
+}
@@ -0,0 +1,104 @@
+use vcsv_lib::{
+    hash, merkelize, parse_csv, run_with_table, Input, Op, QueryRow, QueryValues, Table,
+};
+use vcsv_script::{parse_query, VcsvError};
+
+const ORDERS: &[u8] = b"id,customer_id,amount\n1,c1,100\n2,c2,50\n3,c1,30\n4,c3,70\n5,c9,10\n";
+const CUSTOMERS: &[u8] = b"id,tier\nc1,gold\nc2,silver\nc3,gold\n";
+const REGIONS: &[u8] = b"tier,region\ngold,EU\nsilver,US\n";
+
+fn input(sql: &str, joined: &[&[u8]]) -> Input {
+    Input {
+        csv: ORDERS.to_vec(),
+        col: String::new(),
+        op: Op::Query,
+        query: Some(parse_query(sql).unwrap()),
+        joined: joined.iter().map(|file| file.to_vec()).collect(),
+        ..Default::default()
+    }
+}
+
+fn file_root(csv: &[u8]) -> [u8; 32] {
+    merkelize(&parse_csv(csv.to_vec(), None))
+}
+
+fn roots(query: &QueryValues) -> Vec<[u8; 32]> {
+    query.joinRoots.iter().map(|root| root.0).collect()
+}
+
+fn row(keys: &[&str], values: &[i128]) -> QueryRow {
+    QueryRow {
+        keys: keys.iter().map(|key| key.to_string()).collect(),
+        values: values.to_vec(),
+    }
+}
+
+#[test]
+fn total_order_value_per_customer_tier() {
+    let sql = "SELECT tier, SUM(amount), COUNT(*) FROM orders \
+               JOIN customers ON customer_id = customers.id GROUP BY tier";
+    let (public, table) = run_with_table(input(sql, &[CUSTOMERS]));
+    let Some(Table::Query(query)) = table else {
+        panic!("no query result")
+    };
+
+    // Order 5 has no customer, so the inner join drops it.
+    assert_eq!(public.n_rows, 4);
+    assert_eq!(public.fileRoot, file_root(ORDERS));
+    assert_eq!(roots(&query), vec![file_root(CUSTOMERS)]);
+    assert_eq!(
+        query.rows,
+        vec![row(&["gold"], &[200, 3]), row(&["silver"], &[50, 1])]
+    );
+
+    let canonical = "SELECT tier, SUM(amount), COUNT(*) FROM orders \
+                     JOIN customers ON customer_id = customers.id GROUP BY tier";
+    assert_eq!(query.planHash, hash(canonical.as_bytes()));
+}
+
+#[test]
+fn joins_chain_and_keys_may_come_in_either_order() {
+    let sql = "SELECT region, SUM(amount) FROM orders \
+               JOIN customers ON customers.id = customer_id \
+               JOIN regions ON customers.tier = regions.tier GROUP BY region";
+    let (_, table) = run_with_table(input(sql, &[CUSTOMERS, REGIONS]));
+    let Some(Table::Query(query)) = table else {
+        panic!("no query result")
+    };
+
+    assert_eq!(
+        roots(&query),
+        vec![file_root(CUSTOMERS), file_root(REGIONS)]
+    );
+    assert_eq!(query.rows, vec![row(&["EU"], &[200]), row(&["US"], &[50])]);
+}
+
+#[test]
+#[should_panic(expected = "column id is ambiguous")]
+fn shared_column_names_must_be_qualified() {
+    run_with_table(input(
+        "SELECT COUNT(*) FROM orders JOIN customers ON customer_id = customers.id WHERE id != \"c1\"",
+        &[CUSTOMERS],
+    ));
+}
+
+#[test]
+#[should_panic(expected = "the query joins 1 files but 0 were given")]
+fn every_joined_file_must_be_given() {
+    run_with_table(input(
+        "SELECT COUNT(*) FROM orders JOIN customers ON customer_id = id",
+        &[],
+    ));
+}
+
+#[test]
+#[should_panic(expected = "the query joins 0 files but 1 were given")]
+fn files_the_query_does_not_join_are_rejected() {
+    run_with_table(input("SELECT SUM(amount) FROM orders", &[CUSTOMERS]));
+}
+
+#[test]
+fn a_table_cannot_appear_twice() {
+    let sql = "SELECT COUNT(*) FROM orders JOIN orders ON id = orders.id";
+    assert!(matches!(parse_query(sql), Err(VcsvError::InvalidQuery(_))));
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use std::{env, fs};
+
+use common::tmpdir;
+use vcsv_script::{BackendConfig, KeySource};
+
+#[test]
+fn key_file_is_trimmed() {
+    let dir = tmpdir();
+    let path = dir.join("network.key");
+    fs::write(&path, "  0xabc123\n").unwrap();
+
+    assert_eq!(KeySource::File(path).read().unwrap(), "0xabc123");
+}
+
+#[test]
+fn key_env_is_read_without_modifying_it() {
+    env::set_var("VCSV_TEST_NETWORK_KEY", "0xdef456");
+
+    let key = KeySource::Env("VCSV_TEST_NETWORK_KEY".to_string())
+        .read()
+        .unwrap();
+    assert_eq!(key, "0xdef456");
+    assert_eq!(env::var("VCSV_TEST_NETWORK_KEY").unwrap(), "0xdef456");
+}
+
+#[test]
+fn missing_or_empty_key_is_an_error() {
+    assert!(KeySource::Env("VCSV_TEST_UNSET_KEY".to_string())
+        .read()
+        .is_err());
+
+    let dir = tmpdir();
+    let path = dir.join("empty.key");
+    fs::write(&path, "\n").unwrap();
+    assert!(KeySource::File(path).read().is_err());
+}
+
+#[test]
+fn backend_debug_redacts_key() {
+    let backend = BackendConfig::Network {
+        private_key: "0xsecret".to_string(),
+    };
+
+    assert!(!format!("{backend:?}").contains("secret"));
+}
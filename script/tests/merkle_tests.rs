@@ -1,28 +1,18 @@
-use std::{
-    env, fs,
-    path::PathBuf,
-    time::{SystemTime, UNIX_EPOCH},
-};
+mod common;
 
+use std::{fs, path::PathBuf};
+
+use common::tmpdir;
 use hex::encode as hex_encode;
-use vcsv_lib::hash;
-use vcsv_script::{inclusion_proof, verify_inclusion, InclusionProofString};
-
-fn tmpdir() -> PathBuf {
-    let nonce = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos();
-    let dir = env::temp_dir().join(format!("vcsv_test_{nonce}"));
-    fs::create_dir_all(&dir).unwrap();
-    dir
-}
+use serde_json::Value;
+use vcsv_lib::{hash, merkelize, parse_csv};
+use vcsv_script::{file_root, inclusion_proof, verify_inclusion, InclusionProofString};
 
 fn fold_to_root(leaf: [u8; 32], siblings: &[[u8; 32]], mut idx: usize) -> [u8; 32] {
     let mut cur = leaf;
     for sib in siblings {
         let mut buf = [0u8; 64];
-        if idx % 2 == 0 {
+        if idx.is_multiple_of(2) {
             buf[..32].copy_from_slice(&cur);
             buf[32..].copy_from_slice(sib);
         } else {
@@ -42,7 +32,7 @@ fn inclusion_round_trip_ok() {
     fs::write(&path, "id,price,qty\n1,120,3\n2,80,1\n3,200,5\n").unwrap();
 
     let row: usize = 1;
-    let proof_bytes = inclusion_proof(path.clone(), row);
+    let proof_bytes = inclusion_proof(path.clone(), row).unwrap();
     assert!(!proof_bytes.siblings.is_empty());
 
     let root = fold_to_root(proof_bytes.leaf, &proof_bytes.siblings, row);
@@ -56,7 +46,7 @@ fn inclusion_round_trip_ok() {
             .collect(),
     };
 
-    assert!(verify_inclusion(&root, proof_hex, row));
+    assert!(verify_inclusion(&root, proof_hex, row).unwrap());
 }
 
 #[test]
@@ -66,7 +56,7 @@ fn inclusion_fails_when_sibling_tampered() {
     fs::write(&path, "id,price,qty\n1,120,3\n2,80,1\n3,200,5\n4,150,2\n").unwrap();
 
     let row: usize = 2;
-    let mut proof_bytes = inclusion_proof(path.clone(), row);
+    let mut proof_bytes = inclusion_proof(path.clone(), row).unwrap();
     let correct_root = fold_to_root(proof_bytes.leaf, &proof_bytes.siblings, row);
 
     proof_bytes.siblings[0][0] ^= 0x01;
@@ -80,7 +70,7 @@ fn inclusion_fails_when_sibling_tampered() {
             .collect(),
     };
 
-    assert!(!verify_inclusion(&correct_root, bad_hex, row));
+    assert!(!verify_inclusion(&correct_root, bad_hex, row).unwrap());
 }
 
 #[test]
@@ -90,7 +80,7 @@ fn inclusion_handles_odd_leaf_duplication() {
     fs::write(&path, "id,price,qty\n1,10,1\n2,20,2\n3,30,3\n").unwrap();
 
     let row: usize = 2;
-    let proof_bytes = inclusion_proof(path.clone(), row);
+    let proof_bytes = inclusion_proof(path.clone(), row).unwrap();
     let root = fold_to_root(proof_bytes.leaf, &proof_bytes.siblings, row);
 
     let proof_hex = InclusionProofString {
@@ -102,5 +92,72 @@ fn inclusion_handles_odd_leaf_duplication() {
             .collect(),
     };
 
-    assert!(verify_inclusion(&root, proof_hex, row));
+    assert!(verify_inclusion(&root, proof_hex, row).unwrap());
+}
+
+#[test]
+fn inclusion_matches_solidity_vectors() {
+    let repo = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..");
+    let json = fs::read_to_string(repo.join("contracts/src/fixtures/merkle-vectors.json")).unwrap();
+    let fixture: Value = serde_json::from_str(&json).unwrap();
+
+    let vectors = fixture["vectors"].as_array().unwrap();
+    assert_eq!(fixture["count"].as_u64().unwrap(), vectors.len() as u64);
+
+    for v in vectors {
+        let path = repo.join(v["file"].as_str().unwrap());
+        let row = v["index"].as_u64().unwrap() as usize;
+
+        let csv = parse_csv(fs::read(&path).unwrap(), None);
+        let root = merkelize(&csv);
+        assert_eq!(v["root"], format!("0x{}", hex_encode(root)));
+        assert_eq!(
+            v["row"],
+            format!(
+                "0x{}",
+                hex_encode(csv.cells(row).collect::<Vec<_>>().join(","))
+            )
+        );
+
+        let proof_bytes = inclusion_proof(path, row).unwrap();
+        let proof_hex: InclusionProofString = serde_json::from_value(v.clone()).unwrap();
+        assert_eq!(
+            proof_hex.leaf,
+            format!("0x{}", hex_encode(proof_bytes.leaf))
+        );
+        assert_eq!(proof_hex.siblings.len(), proof_bytes.siblings.len());
+
+        assert!(verify_inclusion(&root, proof_hex, row).unwrap());
+    }
+}
+
+#[test]
+fn streaming_matches_in_memory_tree() {
+    let dir = tmpdir();
+
+    for rows in 1..=33usize {
+        let mut csv = String::from("a, b ,c\n");
+        for i in 0..rows {
+            // Stray whitespace, CRLF endings and blank lines must not change the root.
+            csv.push_str(&format!(" {i},{} , x{i}\r\n", i * 7));
+            if i % 5 == 0 {
+                csv.push('\n');
+            }
+        }
+        let path = dir.join(format!("rows_{rows}.csv"));
+        fs::write(&path, &csv).unwrap();
+
+        let root = merkelize(&parse_csv(csv.into_bytes(), None));
+        assert_eq!(file_root(&path).unwrap(), root, "{rows} rows");
+
+        for row in 0..rows {
+            let proof = inclusion_proof(&path, row).unwrap();
+            assert_eq!(
+                fold_to_root(proof.leaf, &proof.siblings, row),
+                root,
+                "row {row} of {rows}"
+            );
+        }
+        assert!(inclusion_proof(&path, rows).is_err());
+    }
 }
@@ -0,0 +1,53 @@
+mod common;
+
+use clap::ValueEnum;
+use common::write_csv;
+use vcsv_lib::{Op, ProofType};
+use vcsv_script::{proof_type_of, read_input, BackendConfig, ProofBundle, VcsvClient};
+
+#[test]
+#[cfg_attr(not(guest_programs), ignore = "the guest programs were not built")]
+fn mock_prove_and_verify_every_proof_type() {
+    let path = write_csv("id,price,qty\n1,120,3\n2,80,1\n3,200,5\n");
+
+    let client = VcsvClient::new(BackendConfig::Mock);
+    let input = read_input(&path, Op::Sum, "price").unwrap();
+
+    for proof_type in ProofType::value_variants() {
+        let out = path.with_file_name(format!("{proof_type:?}.json"));
+
+        client
+            .prove(&input, *proof_type)
+            .unwrap()
+            .write(&out)
+            .unwrap();
+
+        let bundle = ProofBundle::read(&out).unwrap();
+        assert_eq!(bundle.proof_type, *proof_type);
+        assert_eq!(proof_type_of(&bundle.proof.proof), *proof_type);
+
+        let public = client.verify(&bundle).unwrap();
+        assert_eq!(public.n_rows, 3);
+        assert_eq!(public.result, 400);
+        assert_eq!(public.decimal_points, 0);
+    }
+}
+
+#[test]
+#[cfg_attr(not(guest_programs), ignore = "the guest programs were not built")]
+fn execute_returns_public_values() {
+    let path = write_csv("id,price,qty\n1,120,3\n2,80,1\n3,200,5\n");
+
+    let client = VcsvClient::new(BackendConfig::Mock);
+    let execution = client
+        .execute(&read_input(&path, Op::Mean, "price").unwrap())
+        .unwrap();
+
+    assert_eq!(execution.public_values.n_rows, 3);
+    assert_eq!(execution.public_values.result, 133_333);
+    assert_eq!(execution.public_values.decimal_points, 3);
+    assert!(execution.report.total_instruction_count() > 0);
+
+    let missing = read_input(&path, Op::Sum, "nope").unwrap();
+    assert!(client.execute(&missing).is_err());
+}
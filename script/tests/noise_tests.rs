@@ -0,0 +1,73 @@
+mod common;
+
+use common::csv;
+use vcsv_lib::{hash, run, Input, Noise, Op};
+
+fn input(op: Op, noise: Option<Noise>) -> Input {
+    Input {
+        csv: csv(20),
+        col: "price".to_string(),
+        op,
+        noise,
+        ..Default::default()
+    }
+}
+
+fn noise(seed: u8) -> Noise {
+    Noise {
+        epsilon_millis: 1000,
+        lower: 0,
+        upper: 100,
+        seed: [seed; 32],
+    }
+}
+
+#[test]
+fn noise_is_committed_and_deterministic() {
+    let exact = run(input(Op::Sum, None));
+    assert_eq!(exact.epsilon_millis, 0);
+    assert_eq!(exact.seedHash, [0u8; 32]);
+
+    let noised = run(input(Op::Sum, Some(noise(1))));
+    assert_eq!(noised, run(input(Op::Sum, Some(noise(1)))));
+    assert_eq!(noised.n_rows, exact.n_rows);
+    assert_eq!(noised.epsilon_millis, 1000);
+    assert_eq!(noised.sensitivity, 100);
+    assert_eq!(noised.seedHash, hash(&[1; 32]));
+
+    // The mean is the noised sum divided by the row count.
+    let mean = run(input(Op::Mean, Some(noise(1))));
+    assert_eq!(mean.decimal_points, 3);
+    assert!((mean.result - exact.result * 1000 / 20).abs() < 1000 * 1000);
+}
+
+#[test]
+fn noise_follows_the_geometric_mechanism() {
+    let exact = run(input(Op::Sum, None)).result;
+    let draws: Vec<i128> = (0..=255)
+        .map(|seed| run(input(Op::Sum, Some(noise(seed)))).result - exact)
+        .collect();
+
+    // With ε = 1 and sensitivity 100 the noise is centred on zero with a mean absolute value
+    // of about 100.
+    let mean = draws.iter().sum::<i128>() as f64 / draws.len() as f64;
+    let mean_abs = draws.iter().map(|d| d.abs()).sum::<i128>() as f64 / draws.len() as f64;
+    assert!(mean.abs() < 25.0, "mean {mean}");
+    assert!(
+        (60.0..140.0).contains(&mean_abs),
+        "mean absolute {mean_abs}"
+    );
+    assert!(draws.iter().any(|d| *d != draws[0]));
+}
+
+#[test]
+#[should_panic(expected = "outside the noise bounds")]
+fn noise_rejects_values_outside_the_bounds() {
+    run(input(
+        Op::Sum,
+        Some(Noise {
+            upper: 50,
+            ..noise(1)
+        }),
+    ));
+}
@@ -0,0 +1,93 @@
+use vcsv_lib::{hash, op_to_u8, run, Input, Op};
+
+fn input(op: Op, csv: &[u8]) -> Input {
+    Input {
+        csv: csv.to_vec(),
+        col: "x".to_string(),
+        op,
+        y_col: Some("y".to_string()),
+        ..Default::default()
+    }
+}
+
+const CSV: &[u8] = b"id,x,y\n1,1,2\n2,2,4\n3,3,5\n4,4,4\n5,5,5\n";
+
+#[test]
+fn pairwise_statistics_are_exact() {
+    let public = run(input(Op::Covariance, CSV));
+    assert_eq!(public.op, op_to_u8(Op::Covariance));
+    assert_eq!(public.n_rows, 5);
+    assert_eq!(public.decimal_points, 3);
+    assert_eq!(public.result, 1_200);
+
+    assert_eq!(run(input(Op::Slope, CSV)).result, 600);
+    assert_eq!(run(input(Op::Intercept, CSV)).result, 2_200);
+    // 30 / √1500 = 0.774597
+    assert_eq!(run(input(Op::Correlation, CSV)).result, 775);
+}
+
+#[test]
+fn negative_relationships_round_away_from_zero() {
+    let csv = b"x,y\n1,-2\n2,-4\n3,-5\n4,-4\n5,-5\n";
+    assert_eq!(run(input(Op::Correlation, csv)).result, -775);
+    assert_eq!(run(input(Op::Slope, csv)).result, -600);
+    assert_eq!(run(input(Op::Intercept, csv)).result, -2_200);
+}
+
+#[test]
+fn both_column_hashes_are_committed() {
+    let public = run(input(Op::Correlation, CSV));
+    assert_eq!(public.colHash, hash(b"x"));
+    assert_eq!(public.yColHash, hash(b"y"));
+
+    let public = run(input(Op::Sum, CSV));
+    assert_eq!(public.yColHash, [0u8; 32]);
+}
+
+#[test]
+#[should_panic(expected = "the x column is constant")]
+fn slope_of_a_constant_column() {
+    run(input(Op::Slope, b"x,y\n3,1\n3,2\n"));
+}
+
+#[test]
+#[should_panic(expected = "pairwise statistics need a y column")]
+fn pairwise_statistics_require_a_y_column() {
+    run(Input {
+        y_col: None,
+        ..input(Op::Covariance, CSV)
+    });
+}
+
+#[test]
+fn weighted_mean_and_sum_product() {
+    let tiny = include_bytes!("../../examples/tiny.csv");
+    let weighted = |op| Input {
+        col: "price".to_string(),
+        y_col: Some("qty".to_string()),
+        ..input(op, tiny)
+    };
+
+    let public = run(weighted(Op::SumProduct));
+    assert_eq!(public.n_rows, 10);
+    assert_eq!(public.decimal_points, 0);
+    assert_eq!(public.yColHash, hash(b"qty"));
+    assert_eq!(public.result, 4_285);
+
+    // 4285 / 28 = 153.0357
+    let public = run(weighted(Op::WeightedMean));
+    assert_eq!(public.decimal_points, 3);
+    assert_eq!(public.result, 153_036);
+}
+
+#[test]
+#[should_panic(expected = "weight -1 is negative")]
+fn weights_must_not_be_negative() {
+    run(input(Op::WeightedMean, b"x,y\n10,2\n20,-1\n"));
+}
+
+#[test]
+#[should_panic(expected = "the weights add up to zero")]
+fn weights_must_not_all_be_zero() {
+    run(input(Op::WeightedMean, b"x,y\n10,0\n20,0\n"));
+}
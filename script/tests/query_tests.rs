@@ -0,0 +1,90 @@
+use vcsv_lib::{hash, op_to_u8, run_with_table, Input, Op, QueryRow, Table};
+use vcsv_script::{parse_query, VcsvError};
+
+const CSV: &[u8] = b"region,price,qty\nEU,100,2\nUS,50,3\nEU,10,1\nUS,70,2\nAPAC,30,5\n";
+
+fn input(sql: &str, min_rows: u64) -> Input {
+    Input {
+        csv: CSV.to_vec(),
+        col: String::new(),
+        op: Op::Query,
+        min_rows,
+        query: Some(parse_query(sql).unwrap()),
+        ..Default::default()
+    }
+}
+
+fn row(keys: &[&str], values: &[i128]) -> QueryRow {
+    QueryRow {
+        keys: keys.iter().map(|key| key.to_string()).collect(),
+        values: values.to_vec(),
+    }
+}
+
+#[test]
+fn grouped_query() {
+    let sql = "SELECT region, AVG(price) FROM data WHERE qty > 1 GROUP BY region";
+    let (public, table) = run_with_table(input(sql, 0));
+    let Some(Table::Query(query)) = table else {
+        panic!("no query result")
+    };
+
+    assert_eq!(public.op, op_to_u8(Op::Query));
+    assert_eq!(public.n_rows, 4);
+    assert_eq!(public.result, 3);
+    assert_eq!(public.colHash, [0u8; 32]);
+
+    let canonical = "SELECT region, AVG(price) FROM data WHERE (qty > 1) GROUP BY region";
+    assert_eq!(parse_query(sql).unwrap().to_string(), canonical);
+    assert_eq!(query.planHash, hash(canonical.as_bytes()));
+    assert_eq!(query.columns, vec!["region", "AVG(price)"]);
+    assert_eq!(query.decimal_points, vec![3]);
+    assert_eq!(
+        query.rows,
+        vec![
+            row(&["APAC"], &[30_000]),
+            row(&["EU"], &[100_000]),
+            row(&["US"], &[60_000]),
+        ]
+    );
+}
+
+#[test]
+fn ungrouped_query() {
+    let sql = "select count(*), sum(price * qty), min(price), max(price) from sales;";
+    let (public, table) = run_with_table(input(sql, 0));
+    let Some(Table::Query(query)) = table else {
+        panic!("no query result")
+    };
+
+    assert_eq!(public.n_rows, 5);
+    assert_eq!(query.decimal_points, vec![0, 0, 0, 0]);
+    assert_eq!(query.rows, vec![row(&[], &[5, 650, 10, 100])]);
+}
+
+#[test]
+#[should_panic(expected = "cohort of 1 rows is below the minimum of 2")]
+fn every_group_must_meet_the_minimum() {
+    run_with_table(input(
+        "SELECT region, COUNT(*) FROM data GROUP BY region",
+        2,
+    ));
+}
+
+#[test]
+fn invalid_queries_are_rejected() {
+    for sql in [
+        "SELECT region FROM data GROUP BY region",
+        "SELECT region, SUM(price) FROM data",
+        "SELECT MEDIAN(price) FROM data",
+        "SELECT SUM(price) data",
+        "SELECT COUNT(qty) FROM data",
+        "SELECT SUM(price) FROM data WHERE",
+        "SELECT SUM(price) FROM data GROUP region",
+    ] {
+        assert!(
+            matches!(parse_query(sql), Err(VcsvError::InvalidQuery(_))),
+            "{sql}"
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use vcsv_lib::{op_to_u8, run, Input, Op};
+
+fn input(bounds: (i128, i128)) -> Input {
+    Input {
+        csv: b"id,age\n1,34\n2,17\n3,121\n4,18\n5,120\n".to_vec(),
+        col: "age".to_string(),
+        op: Op::RangeCheck,
+        bounds: Some(bounds),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn range_check_counts_violations() {
+    let public = run(input((18, 120)));
+    assert_eq!(public.op, op_to_u8(Op::RangeCheck));
+    assert_eq!(public.n_rows, 5);
+    assert_eq!(public.result, 2);
+    assert_eq!((public.lower, public.upper), (18, 120));
+
+    let public = run(input((0, 200)));
+    assert_eq!(public.result, 0);
+}
+
+#[test]
+#[should_panic(expected = "range check needs bounds")]
+fn range_check_requires_bounds() {
+    run(Input {
+        bounds: None,
+        ..input((0, 0))
+    });
+}
@@ -0,0 +1,84 @@
+use alloy_primitives::Address;
+use vcsv_lib::{merkelize, parse_csv, recover_signer, run, Input, Op};
+use vcsv_script::{
+    parse_signature, sign_root, signing_key, verify_root_signature, BackendConfig, VcsvClient,
+};
+
+// The example account from the web3.js documentation.
+const KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const ADDRESS: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+
+#[test]
+fn signed_root_recovers_the_signer() {
+    let root = [7u8; 32];
+    let signature = sign_root(&root, &signing_key(KEY).unwrap());
+
+    assert_eq!(signature.signer, ADDRESS);
+    assert!(verify_root_signature(&root, &signature).unwrap());
+
+    let recovered = recover_signer(&root, &parse_signature(&signature.signature).unwrap());
+    assert_eq!(recovered.unwrap().to_checksum(None), ADDRESS);
+}
+
+#[test]
+fn signature_does_not_verify_for_another_root_or_signer() {
+    let root = [7u8; 32];
+    let signature = sign_root(&root, &signing_key(KEY).unwrap());
+
+    assert!(!verify_root_signature(&[8u8; 32], &signature).unwrap());
+
+    let mut forged = signature.clone();
+    forged.signer = "0x0000000000000000000000000000000000000001".to_string();
+    assert!(!verify_root_signature(&root, &forged).unwrap());
+
+    assert!(signing_key("0x1234").is_err());
+}
+
+#[test]
+fn run_commits_the_signer_of_the_root() {
+    let csv = b"id,price,qty\n1,120,3\n2,80,1\n".to_vec();
+    let root = merkelize(&parse_csv(csv.clone(), None));
+    let signature = sign_root(&root, &signing_key(KEY).unwrap());
+
+    let mut input = Input {
+        csv,
+        col: "price".to_string(),
+        op: Op::Sum,
+        ..Default::default()
+    };
+    assert_eq!(run(input.clone()).signer, Address::ZERO);
+
+    input.signature = Some(parse_signature(&signature.signature).unwrap().to_vec());
+    assert_eq!(run(input).signer.to_checksum(None), ADDRESS);
+}
+
+#[test]
+#[cfg_attr(not(guest_programs), ignore = "the guest programs were not built")]
+fn guest_recovers_the_signer_on_the_secp256k1_precompile() {
+    let csv = b"id,price,qty\n1,120,3\n2,80,1\n".to_vec();
+    let root = merkelize(&parse_csv(csv.clone(), None));
+    let signature = sign_root(&root, &signing_key(KEY).unwrap());
+    let input = Input {
+        csv,
+        col: "price".to_string(),
+        op: Op::Sum,
+        signature: Some(parse_signature(&signature.signature).unwrap().to_vec()),
+        ..Default::default()
+    };
+
+    let execution = VcsvClient::new(BackendConfig::Cpu).execute(&input).unwrap();
+    assert_eq!(execution.public_values.signer.to_checksum(None), ADDRESS);
+
+    // Software recovery makes no secp256k1 syscalls, so this fails without the patched k256.
+    let secp256k1_calls: u64 = execution
+        .report
+        .syscall_counts
+        .iter()
+        .filter(|(code, _)| format!("{code:?}").starts_with("SECP256K1"))
+        .map(|(_, count)| count)
+        .sum();
+    assert!(
+        secp256k1_calls > 0,
+        "signer recovery did not use the secp256k1 precompile"
+    );
+}
@@ -0,0 +1,85 @@
+use vcsv_lib::{hash_row, op_to_u8, run, run_with_table, Input, Op, Table};
+use vcsv_script::{file_root, inclusion_proof};
+
+const CSV: &[u8] = b"id,amount\n1,50\n2,300\n3,120\n4,300\n5,10\n";
+const TINY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../examples/tiny.csv");
+
+fn input(op: Op, k: u64, reveal_rows: bool) -> Input {
+    Input {
+        csv: CSV.to_vec(),
+        col: "amount".to_string(),
+        op,
+        k,
+        reveal_rows,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn top_and_bottom_k_sum_the_selected_values() {
+    let public = run(input(Op::TopK, 3, false));
+    assert_eq!(public.op, op_to_u8(Op::TopK));
+    assert_eq!(public.n_rows, 5);
+    assert_eq!(public.k, 3);
+    assert_eq!(public.decimal_points, 0);
+    assert_eq!(public.result, 720);
+
+    assert_eq!(run(input(Op::BottomK, 2, false)).result, 60);
+    assert_eq!(run(input(Op::TopK, 5, false)).result, 780);
+}
+
+#[test]
+fn rows_are_only_committed_when_revealed() {
+    assert_eq!(run_with_table(input(Op::TopK, 2, false)).1, None);
+
+    let (public, table) = run_with_table(input(Op::TopK, 2, true));
+    let Some(Table::TopK(top_k)) = table else {
+        panic!("no rows revealed")
+    };
+    assert_eq!(public.result, 600);
+    // The tie at 300 goes to the earlier row.
+    assert_eq!(top_k.rows, vec![1, 3]);
+    assert_eq!(top_k.values, vec![300, 300]);
+    assert_eq!(top_k.leaves[0], hash_row("2,300"));
+    assert_eq!(top_k.leaves[1], hash_row("4,300"));
+
+    let (_, table) = run_with_table(input(Op::BottomK, 2, true));
+    let Some(Table::TopK(bottom_k)) = table else {
+        panic!("no rows revealed")
+    };
+    assert_eq!(bottom_k.rows, vec![4, 0]);
+    assert_eq!(bottom_k.values, vec![10, 50]);
+}
+
+#[test]
+fn revealed_rows_link_to_the_file_root() {
+    let (public, table) = run_with_table(Input {
+        csv: std::fs::read(TINY).unwrap(),
+        col: "price".to_string(),
+        ..input(Op::TopK, 3, true)
+    });
+    let Some(Table::TopK(top_k)) = table else {
+        panic!("no rows revealed")
+    };
+    assert_eq!(public.fileRoot, file_root(TINY).unwrap());
+    assert_eq!(public.result, 950);
+    assert_eq!(top_k.rows, vec![7, 5, 8]);
+    for (row, leaf) in top_k.rows.iter().zip(&top_k.leaves) {
+        assert_eq!(inclusion_proof(TINY, *row as usize).unwrap().leaf, leaf.0);
+    }
+}
+
+#[test]
+#[should_panic(expected = "k is larger than the 5 rows")]
+fn k_cannot_exceed_the_rows() {
+    run(input(Op::TopK, 6, false));
+}
+
+#[test]
+#[should_panic(expected = "cohort of 2 rows is below the minimum of 3")]
+fn k_must_meet_the_minimum_cohort() {
+    run(Input {
+        min_rows: 3,
+        ..input(Op::TopK, 2, true)
+    });
+}